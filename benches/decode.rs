@@ -0,0 +1,55 @@
+//! Compares decoding a full page of games with path tracking (how every
+//! response used to be decoded), without it (the owned models now), and into
+//! the borrowed models. Run with `cargo bench --bench decode`.
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use srcapi_plus::games::{borrowed, Game};
+use srcapi_plus::pagination::Page;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const ITERATIONS: u32 = 500;
+
+fn bench(name: &str, mut decode: impl FnMut()) -> Duration {
+    for _ in 0..ITERATIONS / 10 {
+        decode();
+    }
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        decode();
+    }
+    let per_page = start.elapsed() / ITERATIONS;
+
+    println!("{:<16} {:>10.1?} per page", name, per_page);
+    per_page
+}
+
+fn main() {
+    let games: Vec<String> = (0..200).map(|i| common::game(&format!("g{}", i))).collect();
+    let body = common::page(&games).into_bytes();
+
+    let tracked = bench("path-tracked", || {
+        let de = &mut serde_json::Deserializer::from_slice(&body);
+        let page: Page<Game> = serde_path_to_error::deserialize(de).unwrap();
+        black_box(page);
+    });
+
+    let owned = bench("owned", || {
+        let page: Page<Game> = serde_json::from_slice(&body).unwrap();
+        black_box(page);
+    });
+
+    let borrowed = bench("borrowed", || {
+        let page: Page<borrowed::Game> = serde_json::from_slice(&body).unwrap();
+        black_box(page);
+    });
+
+    println!(
+        "owned is {:.2}x and borrowed {:.2}x as fast as path-tracked",
+        tracked.as_secs_f64() / owned.as_secs_f64(),
+        tracked.as_secs_f64() / borrowed.as_secs_f64(),
+    );
+}
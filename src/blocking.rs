@@ -0,0 +1,358 @@
+//! A synchronous wrapper around [`SrcClient`](crate::client::SrcClient), for
+//! programs that don't run an async runtime of their own.
+//!
+//! Every call blocks the current thread until it completes. The client must
+//! not be used from within an async runtime.
+
+#[cfg(feature = "metrics")]
+use crate::metrics::Metrics;
+use crate::transport::Url;
+use crate::{
+    cache::Cache,
+    client::{self, SrcClientBuilder},
+    error::SrcError,
+    games::Game,
+    pagination::Paging,
+    query::{OrderBy, OrderDirection, QueryData},
+    ratelimit::{Priority, RateLimiter},
+    resource::Resource,
+    response::Response,
+    runs::PersonalBest,
+    users::User,
+};
+use bytes::Bytes;
+use futures::{Stream, StreamExt};
+use serde::de::DeserializeOwned;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::runtime::{Builder, Runtime};
+
+/// The blocking counterpart of [`client::SrcClient`], with the same methods.
+///
+/// Clones share the runtime, connection pool, rate limiter and cache.
+#[derive(Clone)]
+pub struct SrcClient {
+    inner: client::SrcClient,
+    runtime: Arc<Runtime>,
+}
+
+#[cfg(feature = "reqwest")]
+impl Default for SrcClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SrcClient {
+    /// A client with the default configuration, sending requests with the
+    /// bundled `reqwest` transport. Without that feature, set a transport on
+    /// the [builder](SrcClientBuilder::transport) instead.
+    #[cfg(feature = "reqwest")]
+    pub fn new() -> Self {
+        Self::from_async(client::SrcClient::new()).expect("failed to start a tokio runtime")
+    }
+
+    /// The async builder; finish it with [`SrcClientBuilder::build_blocking`].
+    pub fn builder() -> SrcClientBuilder {
+        SrcClientBuilder::new()
+    }
+
+    /// Wraps an async client, starting a runtime to drive its requests.
+    pub fn from_async(inner: client::SrcClient) -> Result<Self, SrcError> {
+        let runtime = Builder::new_current_thread().enable_all().build()?;
+
+        Ok(Self {
+            inner,
+            runtime: Arc::new(runtime),
+        })
+    }
+
+    /// The async client behind this one.
+    pub fn inner(&self) -> &client::SrcClient {
+        &self.inner
+    }
+
+    pub fn base_url(&self) -> &Url {
+        self.inner.base_url()
+    }
+
+    /// See [`client::SrcClient::with_api_key`].
+    pub fn with_api_key(&self, api_key: impl Into<String>) -> Self {
+        Self {
+            inner: self.inner.with_api_key(api_key),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    /// See [`client::SrcClient::with_profile`].
+    pub fn with_profile(&self, name: &str) -> Result<Self, SrcError> {
+        Ok(Self {
+            inner: self.inner.with_profile(name)?,
+            runtime: self.runtime.clone(),
+        })
+    }
+
+    pub fn anonymous(&self) -> Self {
+        Self {
+            inner: self.inner.anonymous(),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    pub fn profiles(&self) -> impl Iterator<Item = &str> {
+        self.inner.profiles()
+    }
+
+    pub fn identity(&self) -> Option<&str> {
+        self.inner.identity()
+    }
+
+    /// See [`client::SrcClient::with_priority`].
+    pub fn with_priority(&self, priority: Priority) -> Self {
+        Self {
+            inner: self.inner.with_priority(priority),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    /// See [`client::SrcClient::with_offline`].
+    pub fn with_offline(&self, offline: bool) -> Self {
+        Self {
+            inner: self.inner.with_offline(offline),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.inner.is_offline()
+    }
+
+    pub fn get_profile(&self) -> Result<Response<User>, SrcError> {
+        self.runtime.block_on(self.inner.get_profile())
+    }
+
+    pub fn cache(&self) -> Option<&Cache> {
+        self.inner.cache()
+    }
+
+    #[cfg(feature = "metrics")]
+    pub fn metrics(&self) -> Option<&Metrics> {
+        self.inner.metrics()
+    }
+
+    pub fn rate_limiter(&self) -> &RateLimiter {
+        self.inner.rate_limiter()
+    }
+
+    pub fn get_user(&self, id: String) -> Result<Response<User>, SrcError> {
+        self.runtime.block_on(self.inner.get_user(id))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn get_users(
+        &self,
+        lookup: Option<String>,
+        name: Option<String>,
+        twitch: Option<String>,
+        hitbox: Option<String>,
+        twitter: Option<String>,
+        speedrunslive: Option<String>,
+        orderby: Option<OrderBy>,
+        direction: Option<OrderDirection>,
+    ) -> Result<Response<Vec<User>>, SrcError> {
+        self.runtime.block_on(self.inner.get_users(
+            lookup,
+            name,
+            twitch,
+            hitbox,
+            twitter,
+            speedrunslive,
+            orderby,
+            direction,
+        ))
+    }
+
+    /// Like [`SrcClient::get_users`], but follows pagination links and yields
+    /// every matching user instead of only the first page. Pages are fetched
+    /// as the iterator reaches them.
+    #[allow(clippy::too_many_arguments)]
+    pub fn get_users_stream(
+        &self,
+        lookup: Option<String>,
+        name: Option<String>,
+        twitch: Option<String>,
+        hitbox: Option<String>,
+        twitter: Option<String>,
+        speedrunslive: Option<String>,
+        orderby: Option<OrderBy>,
+        direction: Option<OrderDirection>,
+        paging: Paging,
+    ) -> impl Iterator<Item = Result<User, SrcError>> + '_ {
+        self.iter(self.inner.get_users_stream(
+            lookup,
+            name,
+            twitch,
+            hitbox,
+            twitter,
+            speedrunslive,
+            orderby,
+            direction,
+            paging,
+        ))
+    }
+
+    pub fn get_user_pbs(
+        &self,
+        id: String,
+        top: Option<i32>,
+        series: Option<String>,
+        game: Option<String>,
+    ) -> Result<Response<Vec<PersonalBest>>, SrcError> {
+        self.runtime
+            .block_on(self.inner.get_user_pbs(id, top, series, game))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn get_games(
+        &self,
+        name: Option<String>,
+        abbreviation: Option<String>,
+        released: Option<i32>,
+        gametype: Option<String>,
+        platform: Option<String>,
+        region: Option<String>,
+        genre: Option<String>,
+        engine: Option<String>,
+        developer: Option<String>,
+        publisher: Option<String>,
+        moderator: Option<String>,
+    ) -> Result<Response<Vec<Game>>, SrcError> {
+        self.runtime.block_on(self.inner.get_games(
+            name,
+            abbreviation,
+            released,
+            gametype,
+            platform,
+            region,
+            genre,
+            engine,
+            developer,
+            publisher,
+            moderator,
+        ))
+    }
+
+    /// Like [`SrcClient::get_games`], but follows pagination links and yields
+    /// every matching game instead of only the first page. Pages are fetched
+    /// as the iterator reaches them.
+    #[allow(clippy::too_many_arguments)]
+    pub fn get_games_stream(
+        &self,
+        name: Option<String>,
+        abbreviation: Option<String>,
+        released: Option<i32>,
+        gametype: Option<String>,
+        platform: Option<String>,
+        region: Option<String>,
+        genre: Option<String>,
+        engine: Option<String>,
+        developer: Option<String>,
+        publisher: Option<String>,
+        moderator: Option<String>,
+        paging: Paging,
+    ) -> impl Iterator<Item = Result<Game, SrcError>> + '_ {
+        self.iter(self.inner.get_games_stream(
+            name,
+            abbreviation,
+            released,
+            gametype,
+            platform,
+            region,
+            genre,
+            engine,
+            developer,
+            publisher,
+            moderator,
+            paging,
+        ))
+    }
+
+    /// See [`client::SrcClient::get`].
+    pub fn get<R>(&self, id: impl Into<R::Id>) -> Result<Response<R>, SrcError>
+    where
+        R: Resource,
+    {
+        self.runtime.block_on(self.inner.get::<R>(id))
+    }
+
+    /// See [`client::SrcClient::list`].
+    pub fn list<R>(&self, filter: R::Query) -> Result<Response<Vec<R>>, SrcError>
+    where
+        R: Resource,
+    {
+        self.runtime.block_on(self.inner.list::<R>(filter))
+    }
+
+    /// See [`client::SrcClient::list_body`].
+    pub fn list_body<R>(&self, filter: R::Query) -> Result<Response<Bytes>, SrcError>
+    where
+        R: Resource,
+    {
+        self.runtime.block_on(self.inner.list_body::<R>(filter))
+    }
+
+    /// Like [`SrcClient::list`], but follows pagination links and yields
+    /// every matching `R` instead of only the first page. Pages are fetched
+    /// as the iterator reaches them.
+    pub fn list_stream<R>(
+        &self,
+        filter: R::Query,
+        paging: Paging,
+    ) -> impl Iterator<Item = Result<R, SrcError>> + '_
+    where
+        R: Resource,
+    {
+        self.iter(self.inner.list_stream::<R>(filter, paging))
+    }
+
+    /// See [`client::SrcClient::query_url`].
+    pub fn query_url(&self, query: &QueryData) -> Result<Url, SrcError> {
+        self.inner.query_url(query)
+    }
+
+    /// See [`client::SrcClient::raw_get`].
+    pub fn raw_get<T>(&self, path: &str, params: &[(&str, &str)]) -> Result<Response<T>, SrcError>
+    where
+        T: DeserializeOwned,
+    {
+        self.runtime.block_on(self.inner.raw_get(path, params))
+    }
+
+    fn iter<'a, T>(&'a self, stream: impl Stream<Item = T> + 'a) -> Iter<'a, T> {
+        Iter {
+            runtime: &self.runtime,
+            stream: Box::pin(stream),
+        }
+    }
+}
+
+impl SrcClientBuilder {
+    /// Builds a [`blocking::SrcClient`](SrcClient) instead of an async one.
+    pub fn build_blocking(self) -> Result<SrcClient, SrcError> {
+        SrcClient::from_async(self.build()?)
+    }
+}
+
+/// Drives a stream on the client's runtime, one item per call to `next`.
+struct Iter<'a, T> {
+    runtime: &'a Runtime,
+    stream: Pin<Box<dyn Stream<Item = T> + 'a>>,
+}
+
+impl<T> Iterator for Iter<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.runtime.block_on(self.stream.next())
+    }
+}
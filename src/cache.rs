@@ -0,0 +1,431 @@
+use crate::error::SrcError;
+#[cfg(feature = "metrics")]
+use crate::metrics::Metrics;
+use crate::middleware::{Middleware, Next};
+use crate::response::Freshness;
+use crate::transport::{
+    header::{HeaderValue, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED},
+    HttpRequest, HttpResponse, Method, StatusCode, StreamingResponse, Url,
+};
+use futures::future::BoxFuture;
+use lru::LruCache;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+mod disk;
+
+pub use crate::query::QueryKind;
+pub use disk::DiskCache;
+
+/// Freshness settings for the response cache of a
+/// [`SrcClient`](crate::client::SrcClient).
+#[derive(Debug, Clone)]
+pub struct CacheConfig {
+    /// How long a response stays fresh unless overridden for its kind.
+    pub default_ttl: Duration,
+    ttls: HashMap<QueryKind, Duration>,
+}
+
+impl CacheConfig {
+    pub fn new(default_ttl: Duration) -> Self {
+        Self {
+            default_ttl,
+            ttls: HashMap::new(),
+        }
+    }
+
+    /// Sets how long responses of the given kind stay fresh.
+    pub fn ttl(mut self, kind: QueryKind, ttl: Duration) -> Self {
+        self.ttls.insert(kind, ttl);
+        self
+    }
+
+    pub fn ttl_for(&self, kind: QueryKind) -> Duration {
+        self.ttls.get(&kind).copied().unwrap_or(self.default_ttl)
+    }
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(60))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Stale responses the API confirmed were unchanged.
+    pub revalidations: u64,
+}
+
+/// A successful response as it is kept by a [`CacheStore`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedResponse {
+    pub status: u16,
+    pub body: String,
+    pub fetched_at: SystemTime,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl CachedResponse {
+    pub fn age(&self) -> Duration {
+        self.fetched_at.elapsed().unwrap_or_default()
+    }
+
+    fn from_response(response: &HttpResponse) -> Self {
+        Self {
+            status: response.status.as_u16(),
+            body: String::from_utf8_lossy(&response.body).into_owned(),
+            fetched_at: SystemTime::now(),
+            etag: response.header(ETAG),
+            last_modified: response.header(LAST_MODIFIED),
+        }
+    }
+
+    pub(crate) fn into_response(self, freshness: Freshness) -> HttpResponse {
+        let age = match freshness {
+            Freshness::Revalidated => Duration::ZERO,
+            _ => self.age(),
+        };
+
+        let mut response = HttpResponse::new(
+            StatusCode::from_u16(self.status).unwrap_or(StatusCode::OK),
+            self.body,
+        );
+
+        for (name, value) in [(ETAG, self.etag), (LAST_MODIFIED, self.last_modified)] {
+            if let Some(value) = value.as_deref().and_then(to_header) {
+                response.headers.insert(name, value);
+            }
+        }
+
+        HttpResponse {
+            freshness,
+            age,
+            ..response
+        }
+    }
+}
+
+/// Storage behind a [`Cache`]. Keys are full request URLs.
+///
+/// Stores only keep responses; deciding whether one is still fresh is left to
+/// the [`Cache`] that owns them. Failing to read or write an entry is treated
+/// like a miss.
+pub trait CacheStore: Send + Sync {
+    fn get(&self, key: &str) -> Option<CachedResponse>;
+
+    fn insert(&self, key: &str, response: CachedResponse);
+
+    fn invalidate(&self, key: &str);
+
+    fn invalidate_prefix(&self, prefix: &str);
+
+    fn clear(&self);
+}
+
+/// The response cache of a [`SrcClient`](crate::client::SrcClient), shared by
+/// all of its clones.
+///
+/// It is the outermost of the client's own [`Middleware`] layers, inside the
+/// ones set on the builder. `GET` requests are answered from it while their
+/// response is fresh, so they never reach retries or rate limiting. Stale
+/// responses are revalidated with the `ETag` or `Last-Modified` they were
+/// stored with, and successful responses are stored for next time.
+///
+/// Incremental pages are answered from it while fresh too, but are neither
+/// revalidated nor stored, since that would mean holding their whole body.
+pub struct Cache {
+    config: CacheConfig,
+    store: Box<dyn CacheStore>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    revalidations: AtomicU64,
+    #[cfg(feature = "metrics")]
+    pub(crate) metrics: Option<Metrics>,
+}
+
+impl Cache {
+    pub fn new(config: CacheConfig, store: impl CacheStore + 'static) -> Self {
+        Self {
+            config,
+            store: Box::new(store),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            revalidations: AtomicU64::new(0),
+            #[cfg(feature = "metrics")]
+            metrics: None,
+        }
+    }
+
+    pub fn config(&self) -> &CacheConfig {
+        &self.config
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            revalidations: self.revalidations.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Removes the responses cached for `url`, the full request URL, for
+    /// every identity that fetched it.
+    pub fn invalidate(&self, url: &str) {
+        self.store.invalidate(url);
+        self.store.invalidate_prefix(&format!("{}#", url));
+    }
+
+    /// Removes every response whose URL starts with `prefix`, e.g.
+    /// `https://www.speedrun.com/api/v1/games` to drop all game listings.
+    pub fn invalidate_prefix(&self, prefix: &str) {
+        self.store.invalidate_prefix(prefix);
+    }
+
+    pub fn clear(&self) {
+        self.store.clear();
+    }
+
+    /// Returns the response cached for `key` and whether it is still fresh
+    /// for `kind`. Stale responses are returned too, so they can be
+    /// revalidated; only fresh ones count as a hit.
+    pub(crate) fn get(&self, key: &str, kind: QueryKind) -> Option<(CachedResponse, bool)> {
+        let ttl = self.config.ttl_for(kind);
+
+        let response = self.store.get(key);
+        let fresh = response.as_ref().is_some_and(|entry| entry.age() < ttl);
+
+        self.count(kind, fresh);
+
+        response.map(|entry| (entry, fresh))
+    }
+
+    /// Returns the response cached for `key` however old it is, for serving
+    /// while offline. Any response found counts as a hit, as it is served
+    /// as is.
+    pub(crate) fn get_offline(&self, key: &str, kind: QueryKind) -> Option<CachedResponse> {
+        let response = self.store.get(key);
+
+        self.count(kind, response.is_some());
+
+        response
+    }
+
+    fn count(
+        &self,
+        #[cfg_attr(not(feature = "metrics"), allow(unused_variables))] kind: QueryKind,
+        hit: bool,
+    ) {
+        match hit {
+            true => self.hits.fetch_add(1, Ordering::Relaxed),
+            false => self.misses.fetch_add(1, Ordering::Relaxed),
+        };
+
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = &self.metrics {
+            metrics.observe_cache(kind, if hit { "hit" } else { "miss" });
+        }
+    }
+
+    /// Marks a stale response as fresh again after a `304 Not Modified`,
+    /// taking over any new validators sent with it.
+    pub(crate) fn revalidate(
+        &self,
+        key: &str,
+        #[cfg_attr(not(feature = "metrics"), allow(unused_variables))] kind: QueryKind,
+        mut response: CachedResponse,
+        not_modified: &HttpResponse,
+    ) -> CachedResponse {
+        self.revalidations.fetch_add(1, Ordering::Relaxed);
+
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = &self.metrics {
+            metrics.observe_cache(kind, "revalidated");
+        }
+
+        response.fetched_at = SystemTime::now();
+
+        if let Some(etag) = not_modified.header(ETAG) {
+            response.etag = Some(etag);
+        }
+        if let Some(date) = not_modified.header(LAST_MODIFIED) {
+            response.last_modified = Some(date);
+        }
+
+        self.store.insert(key, response.clone());
+
+        response
+    }
+
+    pub(crate) fn insert(&self, key: &str, response: CachedResponse) {
+        self.store.insert(key, response);
+    }
+}
+
+/// Answers fresh hits, revalidates stale entries and stores successful
+/// responses, see [`Cache`].
+impl Middleware for Cache {
+    fn handle<'a>(
+        &'a self,
+        mut request: HttpRequest,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<HttpResponse, SrcError>> {
+        Box::pin(async move {
+            if request.method != Method::GET {
+                return next.run(request).await;
+            }
+
+            let key = key(&request.url, request.identity.as_deref());
+            let kind = request.kind;
+
+            let stale = match self.get(&key, kind) {
+                Some((cached, true)) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(age_ms = cached.age().as_millis() as u64, "cache hit");
+
+                    return Ok(cached.into_response(Freshness::Cached));
+                }
+                Some((cached, false)) => Some(cached),
+                None => None,
+            };
+
+            if let Some(cached) = &stale {
+                if let Some(etag) = cached.etag.as_deref().and_then(to_header) {
+                    request.headers.insert(IF_NONE_MATCH, etag);
+                }
+                if let Some(date) = cached.last_modified.as_deref().and_then(to_header) {
+                    request.headers.insert(IF_MODIFIED_SINCE, date);
+                }
+            }
+
+            let response = next.run(request).await?;
+
+            match stale {
+                Some(cached) if response.status == StatusCode::NOT_MODIFIED => {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!("cache entry revalidated");
+
+                    let cached = self.revalidate(&key, kind, cached, &response);
+
+                    Ok(cached.into_response(Freshness::Revalidated))
+                }
+                _ => {
+                    if response.status.is_success() {
+                        self.insert(&key, CachedResponse::from_response(&response));
+                    }
+
+                    Ok(response)
+                }
+            }
+        })
+    }
+
+    fn handle_streaming<'a>(
+        &'a self,
+        request: HttpRequest,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<StreamingResponse, SrcError>> {
+        Box::pin(async move {
+            if request.method == Method::GET {
+                let key = key(&request.url, request.identity.as_deref());
+
+                if let Some((cached, true)) = self.get(&key, request.kind) {
+                    return Ok(cached.into_response(Freshness::Cached).into());
+                }
+            }
+
+            next.run_streaming(request).await
+        })
+    }
+}
+
+/// The key a response to `url` is cached under. Authenticated responses may
+/// differ between accounts, so every identity gets entries of its own.
+pub(crate) fn key(url: &Url, identity: Option<&str>) -> String {
+    match identity {
+        Some(identity) => format!("{}#{}", url, identity),
+        None => url.to_string(),
+    }
+}
+
+fn to_header(value: &str) -> Option<HeaderValue> {
+    HeaderValue::from_str(value).ok()
+}
+
+/// A [`CacheStore`] that keeps up to `capacity` responses in memory, evicting
+/// the least recently used one when full.
+pub struct MemoryCache {
+    entries: Mutex<LruCache<String, CachedResponse>>,
+}
+
+impl MemoryCache {
+    pub fn new(capacity: usize) -> Self {
+        let capacity = NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN);
+
+        Self {
+            entries: Mutex::new(LruCache::new(capacity)),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for MemoryCache {
+    fn default() -> Self {
+        Self::new(1000)
+    }
+}
+
+impl CacheStore for MemoryCache {
+    fn get(&self, key: &str) -> Option<CachedResponse> {
+        self.entries.lock().unwrap().get(key).cloned()
+    }
+
+    fn insert(&self, key: &str, response: CachedResponse) {
+        self.entries.lock().unwrap().put(key.to_string(), response);
+    }
+
+    fn invalidate(&self, key: &str) {
+        self.entries.lock().unwrap().pop(key);
+    }
+
+    fn invalidate_prefix(&self, prefix: &str) {
+        let mut entries = self.entries.lock().unwrap();
+
+        let keys: Vec<String> = entries
+            .iter()
+            .map(|(key, _)| key)
+            .filter(|key| key.starts_with(prefix))
+            .cloned()
+            .collect();
+
+        for key in keys {
+            entries.pop(&key);
+        }
+    }
+
+    fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+}
+
+/// 64-bit FNV-1a, for anything that ends up in a persistent cache, because
+/// unlike `DefaultHasher` it is stable across builds and Rust releases.
+pub(crate) fn fnv1a(key: &str) -> u64 {
+    key.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
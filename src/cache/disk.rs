@@ -0,0 +1,233 @@
+use super::{fnv1a, CacheStore, CachedResponse};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime};
+
+const EXTENSION: &str = "json";
+
+/// Temporary files older than this were left behind by a writer that died
+/// before renaming them into place.
+const STALE_TMP_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// A [`CacheStore`] that keeps one file per response in a directory, so the
+/// cache survives restarts and can be shared between processes.
+///
+/// Entries are written to a temporary file and renamed into place, so readers
+/// never see a partial entry. Once the directory holds more than `max_size`
+/// bytes, the oldest entries are removed until it is back under 90% of it.
+/// Temporary files abandoned by crashed writers are swept up along the way.
+pub struct DiskCache {
+    dir: PathBuf,
+    max_size: u64,
+    writes: AtomicU64,
+    /// Bytes on disk as of the last scan plus this process's writes since,
+    /// so the directory only has to be scanned once it looks full.
+    estimate: AtomicU64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    key: String,
+    response: CachedResponse,
+}
+
+impl DiskCache {
+    /// Opens the cache in `dir`, creating the directory if it is missing.
+    pub fn open(dir: impl Into<PathBuf>, max_size: u64) -> io::Result<Self> {
+        let dir = dir.into();
+
+        fs::create_dir_all(&dir)?;
+
+        let cache = Self {
+            dir,
+            max_size,
+            writes: AtomicU64::new(0),
+            estimate: AtomicU64::new(0),
+        };
+
+        let (entries, tmp_size) = cache.scan();
+        let size = entries.iter().map(|(_, meta)| meta.len()).sum::<u64>() + tmp_size;
+        cache.estimate.store(size, Ordering::Relaxed);
+
+        Ok(cache)
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Total size in bytes of the entries currently on disk.
+    pub fn size(&self) -> u64 {
+        self.scan().0.iter().map(|(_, meta)| meta.len()).sum()
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{:016x}.{}", fnv1a(key), EXTENSION))
+    }
+
+    fn read(&self, path: &Path) -> Option<Entry> {
+        let contents = fs::read(path).ok()?;
+
+        serde_json::from_slice(&contents).ok()
+    }
+
+    /// Writes `entry` to `path`, returning the number of bytes written.
+    fn write(&self, path: &Path, entry: &Entry) -> io::Result<u64> {
+        let contents = serde_json::to_vec(entry)?;
+        let written = contents.len() as u64;
+
+        // unique per process and write, so concurrent writers never share one
+        let tmp = path.with_extension(format!(
+            "{}.{}.tmp",
+            process::id(),
+            self.writes.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        fs::write(&tmp, contents)?;
+
+        fs::rename(&tmp, path).inspect_err(|_| {
+            let _ = fs::remove_file(&tmp);
+        })?;
+
+        Ok(written)
+    }
+
+    /// Lists the entries in the directory, removing stale temporary files on
+    /// the way. Also returns the size of the temporary files that are left.
+    fn scan(&self) -> (Vec<(PathBuf, fs::Metadata)>, u64) {
+        let dir = match fs::read_dir(&self.dir) {
+            Ok(dir) => dir,
+            Err(_) => return (Vec::new(), 0),
+        };
+
+        let mut entries = Vec::new();
+        let mut tmp_size = 0;
+
+        for path in dir.filter_map(Result::ok).map(|entry| entry.path()) {
+            let Ok(meta) = fs::metadata(&path) else {
+                continue;
+            };
+
+            match path.extension().and_then(|ext| ext.to_str()) {
+                Some(EXTENSION) => entries.push((path, meta)),
+                Some("tmp") => {
+                    let age = meta
+                        .modified()
+                        .ok()
+                        .and_then(|modified| modified.elapsed().ok());
+
+                    match age {
+                        Some(age) if age > STALE_TMP_AGE => {
+                            let _ = fs::remove_file(path);
+                        }
+                        _ => tmp_size += meta.len(),
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        (entries, tmp_size)
+    }
+
+    fn entries(&self) -> Vec<(PathBuf, fs::Metadata)> {
+        self.scan().0
+    }
+
+    /// Adds `bytes` to the size estimate, scanning the directory and evicting
+    /// once the estimate goes over `max_size`.
+    fn grow(&self, bytes: u64) {
+        let estimate = self.estimate.fetch_add(bytes, Ordering::Relaxed) + bytes;
+
+        if estimate > self.max_size {
+            self.evict();
+        }
+    }
+
+    fn shrink(&self, bytes: u64) {
+        let _ = self
+            .estimate
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |size| {
+                Some(size.saturating_sub(bytes))
+            });
+    }
+
+    /// Removes the oldest entries until the directory is under 90% of
+    /// `max_size`, leaving room for a few writes before the next scan.
+    fn evict(&self) {
+        let (mut entries, tmp_size) = self.scan();
+        let mut size: u64 = entries.iter().map(|(_, meta)| meta.len()).sum::<u64>() + tmp_size;
+        let target = self.max_size / 10 * 9;
+
+        if size > self.max_size {
+            entries.sort_by_key(|(_, meta)| meta.modified().unwrap_or(SystemTime::UNIX_EPOCH));
+
+            for (path, meta) in entries {
+                if size <= target {
+                    break;
+                }
+
+                // another process may have removed it already
+                let _ = fs::remove_file(path);
+                size = size.saturating_sub(meta.len());
+            }
+        }
+
+        self.estimate.store(size, Ordering::Relaxed);
+    }
+}
+
+impl CacheStore for DiskCache {
+    fn get(&self, key: &str) -> Option<CachedResponse> {
+        self.read(&self.path(key))
+            .filter(|entry| entry.key == key)
+            .map(|entry| entry.response)
+    }
+
+    fn insert(&self, key: &str, response: CachedResponse) {
+        let entry = Entry {
+            key: key.to_string(),
+            response,
+        };
+
+        let path = self.path(key);
+        let replaced = fs::metadata(&path).map_or(0, |meta| meta.len());
+
+        if let Ok(written) = self.write(&path, &entry) {
+            self.shrink(replaced);
+            self.grow(written);
+        }
+    }
+
+    fn invalidate(&self, key: &str) {
+        let path = self.path(key);
+
+        if let Ok(meta) = fs::metadata(&path) {
+            if fs::remove_file(path).is_ok() {
+                self.shrink(meta.len());
+            }
+        }
+    }
+
+    fn invalidate_prefix(&self, prefix: &str) {
+        for (path, meta) in self.entries() {
+            if let Some(entry) = self.read(&path) {
+                if entry.key.starts_with(prefix) && fs::remove_file(path).is_ok() {
+                    self.shrink(meta.len());
+                }
+            }
+        }
+    }
+
+    fn clear(&self) {
+        for (path, _) in self.entries() {
+            let _ = fs::remove_file(path);
+        }
+
+        self.estimate.store(0, Ordering::Relaxed);
+    }
+}
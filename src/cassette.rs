@@ -0,0 +1,195 @@
+use crate::transport::{
+    header::{HeaderMap, HeaderName, HeaderValue, SET_COOKIE},
+    Method, StatusCode, Url,
+};
+use crate::{
+    error::SrcError,
+    middleware::{Middleware, Next},
+    transport::{HttpRequest, HttpResponse},
+};
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Whether a [`Cassette`] captures traffic or plays it back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Requests go to the network and every response is appended to the
+    /// cassette file.
+    Record,
+    /// Requests never reach the network. Responses come from the cassette
+    /// and a request that was not recorded fails with
+    /// [`SrcError::UnmatchedRequest`].
+    Replay,
+}
+
+/// A single recorded request and the response it got.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Interaction {
+    pub method: String,
+    pub url: String,
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+/// A file of recorded HTTP interactions, for running code that uses a
+/// [`SrcClient`](crate::client::SrcClient) offline and deterministically.
+///
+/// Request headers are not recorded, so API keys never end up in a cassette.
+#[derive(Debug)]
+pub struct Cassette {
+    path: PathBuf,
+    mode: CassetteMode,
+    interactions: Mutex<Vec<Interaction>>,
+    played: Mutex<Vec<bool>>,
+}
+
+impl Cassette {
+    /// Starts a new recording, replacing `path` when the first response
+    /// comes in.
+    pub fn record(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            mode: CassetteMode::Record,
+            interactions: Mutex::new(Vec::new()),
+            played: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Loads the interactions recorded in `path` for playback.
+    pub fn replay(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+
+        let interactions: Vec<Interaction> = serde_json::from_slice(&fs::read(&path)?)?;
+        let played = vec![false; interactions.len()];
+
+        Ok(Self {
+            path,
+            mode: CassetteMode::Replay,
+            interactions: Mutex::new(interactions),
+            played: Mutex::new(played),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    pub fn interactions(&self) -> Vec<Interaction> {
+        self.interactions.lock().unwrap().clone()
+    }
+
+    pub(crate) fn record_response(
+        &self,
+        method: &Method,
+        url: &Url,
+        response: &HttpResponse,
+    ) -> Result<(), SrcError> {
+        let headers = response
+            .headers
+            .iter()
+            .filter(|(name, _)| **name != SET_COOKIE)
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect();
+
+        let mut interactions = self.interactions.lock().unwrap();
+
+        interactions.push(Interaction {
+            method: method.to_string(),
+            url: url.to_string(),
+            status: response.status.as_u16(),
+            headers,
+            body: String::from_utf8_lossy(&response.body).into_owned(),
+        });
+
+        let contents = serde_json::to_vec_pretty(&*interactions).map_err(io::Error::from)?;
+
+        fs::write(&self.path, contents)?;
+
+        Ok(())
+    }
+
+    /// Serves the first recorded response for this request that has not been
+    /// played yet. Once all of them have been, the last one is served again.
+    pub(crate) fn replay_response(
+        &self,
+        method: &Method,
+        url: &Url,
+    ) -> Result<HttpResponse, SrcError> {
+        let interactions = self.interactions.lock().unwrap();
+        let mut played = self.played.lock().unwrap();
+
+        let (method, url) = (method.to_string(), url.to_string());
+
+        let matches: Vec<usize> = interactions
+            .iter()
+            .enumerate()
+            .filter(|(_, i)| i.method == method && i.url == url)
+            .map(|(index, _)| index)
+            .collect();
+
+        let index = match matches
+            .iter()
+            .find(|&&index| !played[index])
+            .or(matches.last())
+        {
+            Some(&index) => index,
+            None => return Err(SrcError::UnmatchedRequest { method, url }),
+        };
+
+        played[index] = true;
+
+        let interaction = &interactions[index];
+
+        let mut headers = HeaderMap::new();
+
+        for (name, value) in &interaction.headers {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(value),
+            ) {
+                headers.append(name, value);
+            }
+        }
+
+        let status = StatusCode::from_u16(interaction.status).unwrap_or(StatusCode::OK);
+
+        Ok(HttpResponse {
+            headers,
+            ..HttpResponse::new(status, interaction.body.clone())
+        })
+    }
+}
+
+/// Answers requests from the cassette when replaying, and records the
+/// responses coming back up the chain when recording.
+impl Middleware for Cassette {
+    fn handle<'a>(
+        &'a self,
+        request: HttpRequest,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<HttpResponse, SrcError>> {
+        Box::pin(async move {
+            match self.mode {
+                CassetteMode::Replay => self.replay_response(&request.method, &request.url),
+                CassetteMode::Record => {
+                    let (method, url) = (request.method.clone(), request.url.clone());
+
+                    let response = next.run(request).await?;
+
+                    self.record_response(&method, &url, &response)?;
+
+                    Ok(response)
+                }
+            }
+        })
+    }
+}
@@ -1,31 +1,195 @@
+#[cfg(feature = "metrics")]
+use crate::metrics::Metrics;
+#[cfg(feature = "reqwest")]
+use crate::transport::ReqwestTransport;
 use crate::{
+    cache::{fnv1a, Cache, CacheConfig, CacheStore, MemoryCache},
+    cassette::Cassette,
+    coalesce::InFlight,
+    error::{QueryError, SrcError},
     games::Game,
-    query::{query, OrderBy, OrderDirection, Parameter, QueryData, QueryType},
+    middleware::Middleware,
+    pagination::{paginate, Paging},
+    query::{
+        build_url, query, query_body, GamesQuery, OrderBy, OrderDirection, Parameter, QueryData,
+        QueryType, UsersQuery,
+    },
+    ratelimit::{Priority, RateLimit, RateLimiter, RateLimiters},
+    resource::Resource,
+    response::Response,
+    retry::RetryPolicy,
+    runs::PersonalBest,
+    transport::{
+        header::{HeaderMap, HeaderValue, USER_AGENT},
+        Transport, Url,
+    },
     users::User,
+    BASE_URL,
 };
-use reqwest::Client;
-use serde::{Deserialize, Serialize};
-use std::error::Error;
+use bytes::Bytes;
+use futures::Stream;
+#[cfg(feature = "reqwest")]
+use reqwest::{Client, Proxy};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+#[cfg(feature = "reqwest")]
+use std::time::Duration;
 
+const DEFAULT_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+#[derive(Clone)]
 pub struct SrcClient {
-    pub client: Client,
+    pub(crate) transport: Arc<dyn Transport>,
+    pub(crate) base_url: Url,
+    pub(crate) default_headers: HeaderMap,
+    pub(crate) auth: Option<Auth>,
+    profiles: Arc<HashMap<String, String>>,
+    pub(crate) rate_limiter: RateLimiter,
+    rate_limiters: Arc<RateLimiters>,
+    pub(crate) priority: Priority,
+    pub(crate) middleware: Arc<[Arc<dyn Middleware>]>,
+    pub(crate) cache: Option<Arc<Cache>>,
+    pub(crate) in_flight: Arc<InFlight>,
+    pub(crate) offline: bool,
+    #[cfg(feature = "metrics")]
+    pub(crate) metrics: Option<Metrics>,
+}
+
+#[cfg(feature = "reqwest")]
+impl Default for SrcClient {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl SrcClient {
+    /// A client with the default configuration, sending requests with the
+    /// bundled `reqwest` transport. Without that feature, set a transport on
+    /// the [builder](SrcClientBuilder::transport) instead.
+    #[cfg(feature = "reqwest")]
     pub fn new() -> Self {
+        SrcClientBuilder::new()
+            .build()
+            .expect("the default client configuration is valid")
+    }
+
+    pub fn builder() -> SrcClientBuilder {
+        SrcClientBuilder::new()
+    }
+
+    pub fn base_url(&self) -> &Url {
+        &self.base_url
+    }
+
+    /// Returns a client that sends `api_key` with its requests, sharing the
+    /// connection pool of this one. Its cache entries and rate limit are kept
+    /// apart from those of other keys.
+    pub fn with_api_key(&self, api_key: impl Into<String>) -> Self {
+        self.with_auth(Some(Auth::from_key(api_key.into())))
+    }
+
+    /// Returns a client that makes its requests as the credential profile
+    /// `name`, registered with [`SrcClientBuilder::profile`].
+    pub fn with_profile(&self, name: &str) -> Result<Self, SrcError> {
+        let api_key = self
+            .profiles
+            .get(name)
+            .ok_or_else(|| QueryError::UnknownProfile {
+                name: name.to_string(),
+            })?;
+
+        Ok(self.with_auth(Some(Auth::from_profile(name, api_key.clone()))))
+    }
+
+    /// Returns a client that makes its requests without an API key.
+    pub fn anonymous(&self) -> Self {
+        self.with_auth(None)
+    }
+
+    /// Names of the registered credential profiles.
+    pub fn profiles(&self) -> impl Iterator<Item = &str> {
+        self.profiles.keys().map(String::as_str)
+    }
+
+    /// The identity requests are made as, if any: the profile name followed
+    /// by a hash of its key, or only the hash for clients given a key with
+    /// [`SrcClient::with_api_key`].
+    pub fn identity(&self) -> Option<&str> {
+        self.auth.as_ref().map(|auth| auth.identity.as_str())
+    }
+
+    fn with_auth(&self, auth: Option<Auth>) -> Self {
+        let rate_limiter = self
+            .rate_limiters
+            .get(auth.as_ref().map(|auth| auth.identity.as_str()));
+
+        Self {
+            auth,
+            rate_limiter,
+            ..self.clone()
+        }
+    }
+
+    /// Returns a client whose requests queue at `priority` when they have to
+    /// wait for the shared rate limiter, e.g. to keep interactive requests
+    /// ahead of a background crawl.
+    pub fn with_priority(&self, priority: Priority) -> Self {
         Self {
-            client: Client::new(),
+            priority,
+            ..self.clone()
+        }
+    }
+
+    /// Returns a client that only serves responses from the cache, whatever
+    /// their age, and fails with [`SrcError::Offline`] on a miss instead of
+    /// making a request. Switching it off again restores the usual behavior.
+    pub fn with_offline(&self, offline: bool) -> Self {
+        Self {
+            offline,
+            ..self.clone()
+        }
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.offline
+    }
+
+    /// Returns the user the API key belongs to.
+    pub async fn get_profile(&self) -> Result<Response<User>, SrcError> {
+        if self.auth.is_none() {
+            return Err(QueryError::MissingApiKey.into());
         }
+
+        let q = QueryData::new(QueryType::Profile);
+
+        Ok(query::<Data<User>>(self, &q).await?.map(|u| u.data))
     }
 
-    pub async fn get_user(&self, id: String) -> Result<User, Box<dyn Error>> {
-        let q = QueryData::new(QueryType::User { id });
+    /// The response cache shared by this client and all of its clones, if
+    /// caching was enabled on the builder.
+    pub fn cache(&self) -> Option<&Cache> {
+        self.cache.as_deref()
+    }
 
-        let user_: UserData = query(&self, &q).await?;
+    /// The usage metrics shared by this client and all of its clones, if they
+    /// were enabled on the builder.
+    #[cfg(feature = "metrics")]
+    pub fn metrics(&self) -> Option<&Metrics> {
+        self.metrics.as_ref()
+    }
+
+    /// The limiter shared by all clients making requests as the same identity,
+    /// e.g. to check how much of the request budget is left.
+    pub fn rate_limiter(&self) -> &RateLimiter {
+        &self.rate_limiter
+    }
 
-        Ok(user_.data)
+    pub async fn get_user(&self, id: String) -> Result<Response<User>, SrcError> {
+        self.get::<User>(id).await
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub async fn get_users(
         &self,
         lookup: Option<String>,
@@ -36,35 +200,47 @@ impl SrcClient {
         speedrunslive: Option<String>,
         orderby: Option<OrderBy>,
         direction: Option<OrderDirection>,
-    ) -> Result<Vec<User>, Box<dyn Error>> {
-        let mut q = QueryData::new(QueryType::Users {});
+    ) -> Result<Response<Vec<User>>, SrcError> {
+        self.list::<User>(UsersQuery {
+            lookup,
+            name,
+            twitch,
+            hitbox,
+            twitter,
+            speedrunslive,
+            orderby,
+            direction,
+        })
+        .await
+    }
 
-        if let Some(lookup) = lookup {
-            q.params.push(Parameter::Lookup(lookup.to_string()));
-        }
-        if let Some(name) = name {
-            q.params.push(Parameter::Lookup(name.to_string()));
-        }
-        if let Some(twitch) = twitch {
-            q.params.push(Parameter::Lookup(twitch.to_string()));
-        }
-        if let Some(hitbox) = hitbox {
-            q.params.push(Parameter::Lookup(hitbox.to_string()));
-        }
-        if let Some(twitter) = twitter {
-            q.params.push(Parameter::Lookup(twitter.to_string()));
-        }
-        if let Some(speedrunslive) = speedrunslive {
-            q.params.push(Parameter::Lookup(speedrunslive.to_string()));
-        }
-        if let Some(orderby) = orderby {
-            q.params.push(Parameter::OrderBy(orderby));
-        }
-        if let Some(direction) = direction {
-            q.params.push(Parameter::Direction(direction));
-        }
+    /// Like [`SrcClient::get_users`], but follows pagination links and yields
+    /// every matching user instead of only the first page.
+    #[allow(clippy::too_many_arguments)]
+    pub fn get_users_stream(
+        &self,
+        lookup: Option<String>,
+        name: Option<String>,
+        twitch: Option<String>,
+        hitbox: Option<String>,
+        twitter: Option<String>,
+        speedrunslive: Option<String>,
+        orderby: Option<OrderBy>,
+        direction: Option<OrderDirection>,
+        paging: Paging,
+    ) -> impl Stream<Item = Result<User, SrcError>> + '_ {
+        let filter = UsersQuery {
+            lookup,
+            name,
+            twitch,
+            hitbox,
+            twitter,
+            speedrunslive,
+            orderby,
+            direction,
+        };
 
-        Ok(query::<UsersData>(&self, &q).await?.data)
+        self.list_stream::<User>(filter, paging)
     }
 
     pub async fn get_user_pbs(
@@ -73,7 +249,7 @@ impl SrcClient {
         top: Option<i32>,
         series: Option<String>,
         game: Option<String>,
-    ) -> Result<Vec<User>, Box<dyn Error>> {
+    ) -> Result<Response<Vec<PersonalBest>>, SrcError> {
         let mut q = QueryData::new(QueryType::UserPBs { id });
 
         if let Some(top) = top {
@@ -86,11 +262,12 @@ impl SrcClient {
             q.params.push(Parameter::Game(game))
         }
 
-        let users_: UsersData = query(&self, &q).await?;
-
-        Ok(users_.data)
+        Ok(query::<Data<Vec<PersonalBest>>>(self, &q)
+            .await?
+            .map(|pbs| pbs.data))
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub async fn get_games(
         &self,
         name: Option<String>,
@@ -104,60 +281,439 @@ impl SrcClient {
         developer: Option<String>,
         publisher: Option<String>,
         moderator: Option<String>,
-    ) -> Result<Vec<Game>, Box<dyn Error>> {
-        let mut q = QueryData::new(QueryType::Games);
+    ) -> Result<Response<Vec<Game>>, SrcError> {
+        self.list::<Game>(GamesQuery {
+            name,
+            abbreviation,
+            released,
+            gametype,
+            platform,
+            region,
+            genre,
+            engine,
+            developer,
+            publisher,
+            moderator,
+        })
+        .await
+    }
+
+    /// Like [`SrcClient::get_games`], but follows pagination links and yields
+    /// every matching game instead of only the first page.
+    #[allow(clippy::too_many_arguments)]
+    pub fn get_games_stream(
+        &self,
+        name: Option<String>,
+        abbreviation: Option<String>,
+        released: Option<i32>,
+        gametype: Option<String>,
+        platform: Option<String>,
+        region: Option<String>,
+        genre: Option<String>,
+        engine: Option<String>,
+        developer: Option<String>,
+        publisher: Option<String>,
+        moderator: Option<String>,
+        paging: Paging,
+    ) -> impl Stream<Item = Result<Game, SrcError>> + '_ {
+        let filter = GamesQuery {
+            name,
+            abbreviation,
+            released,
+            gametype,
+            platform,
+            region,
+            genre,
+            engine,
+            developer,
+            publisher,
+            moderator,
+        };
+
+        self.list_stream::<Game>(filter, paging)
+    }
+
+    /// Gets the `R` with the given ID, e.g. `client.get::<Game>("abc")`.
+    pub async fn get<R>(&self, id: impl Into<R::Id>) -> Result<Response<R>, SrcError>
+    where
+        R: Resource,
+    {
+        let q = R::get_query(&id.into());
+
+        Ok(query::<Data<R>>(self, &q).await?.map(|r| r.data))
+    }
+
+    /// Lists the first page of `R`s matching `filter`.
+    pub async fn list<R>(&self, filter: R::Query) -> Result<Response<Vec<R>>, SrcError>
+    where
+        R: Resource,
+    {
+        let q = R::list_query(&filter);
+
+        Ok(query::<Data<Vec<R>>>(self, &q).await?.map(|r| r.data))
+    }
 
-        if let Some(name) = name {
-            q.params.push(Parameter::Name(name))
+    /// Like [`SrcClient::list`], but returns the undecoded page, for decoding
+    /// with [`Response::decode`], e.g. into
+    /// [`Page`](crate::pagination::Page)`<`[`games::borrowed::Game`](crate::games::borrowed::Game)`>`.
+    pub async fn list_body<R>(&self, filter: R::Query) -> Result<Response<Bytes>, SrcError>
+    where
+        R: Resource,
+    {
+        query_body(self, &R::list_query(&filter)).await
+    }
+
+    /// Like [`SrcClient::list`], but follows pagination links and yields
+    /// every matching `R` instead of only the first page.
+    pub fn list_stream<R>(
+        &self,
+        filter: R::Query,
+        paging: Paging,
+    ) -> impl Stream<Item = Result<R, SrcError>> + '_
+    where
+        R: Resource,
+    {
+        paginate(self, R::list_query(&filter), paging)
+    }
+
+    /// The URL `query` would be sent to, without sending it.
+    pub fn query_url(&self, query: &QueryData) -> Result<Url, SrcError> {
+        build_url(&self.base_url, query)
+    }
+
+    /// Gets an endpoint the typed methods don't cover, as a `path` relative
+    /// to the base URL, and decodes the response into any `T`. The request
+    /// goes through the same authentication, rate limiting, caching and
+    /// error handling as the rest.
+    pub async fn raw_get<T>(
+        &self,
+        path: &str,
+        params: &[(&str, &str)],
+    ) -> Result<Response<T>, SrcError>
+    where
+        T: DeserializeOwned,
+    {
+        let mut q = QueryData::new(QueryType::Raw {
+            path: path.to_string(),
+        });
+
+        for (name, value) in params {
+            q.params
+                .push(Parameter::Raw(name.to_string(), value.to_string()));
         }
-        if let Some(abbreviation) = abbreviation {
-            q.params.push(Parameter::Abbreviation(abbreviation))
+
+        query(self, &q).await
+    }
+}
+
+/// The credentials requests are made with.
+#[derive(Clone)]
+pub(crate) struct Auth {
+    /// Names the account in cache keys, rate limiting and telemetry.
+    pub identity: String,
+    pub api_key: String,
+}
+
+impl Auth {
+    /// Credentials without a profile name are identified by a stable hash of
+    /// the key, so the key itself never ends up in a cache and persisted
+    /// entries stay reachable after a toolchain upgrade.
+    fn from_key(api_key: String) -> Self {
+        Self {
+            identity: format!("key-{:016x}", fnv1a(&api_key)),
+            api_key,
         }
-        if let Some(released) = released {
-            q.params.push(Parameter::Released(released))
+    }
+
+    /// Profiles are identified by name and key, so two processes that give
+    /// the same name to different keys, or a profile whose key was rotated,
+    /// never share cache entries.
+    fn from_profile(name: &str, api_key: String) -> Self {
+        Self {
+            identity: format!("{}-{:016x}", name, fnv1a(&api_key)),
+            api_key,
         }
-        if let Some(gametype) = gametype {
-            q.params.push(Parameter::Gametype(gametype))
+    }
+}
+
+pub struct SrcClientBuilder {
+    base_url: String,
+    user_agent: String,
+    #[cfg(feature = "reqwest")]
+    connect_timeout: Option<Duration>,
+    #[cfg(feature = "reqwest")]
+    timeout: Option<Duration>,
+    #[cfg(feature = "reqwest")]
+    proxy: Option<Proxy>,
+    default_headers: HeaderMap,
+    api_key: Option<String>,
+    profiles: HashMap<String, String>,
+    transport: Option<Arc<dyn Transport>>,
+    rate_limit: RateLimit,
+    retry_policy: RetryPolicy,
+    middleware: Vec<Arc<dyn Middleware>>,
+    cache: Option<Cache>,
+    cassette: Option<Cassette>,
+    offline: bool,
+    #[cfg(feature = "metrics")]
+    metrics: Option<Metrics>,
+}
+
+impl SrcClientBuilder {
+    pub fn new() -> Self {
+        Self {
+            base_url: BASE_URL.to_string(),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            #[cfg(feature = "reqwest")]
+            connect_timeout: None,
+            #[cfg(feature = "reqwest")]
+            timeout: None,
+            #[cfg(feature = "reqwest")]
+            proxy: None,
+            default_headers: HeaderMap::new(),
+            api_key: None,
+            profiles: HashMap::new(),
+            transport: None,
+            rate_limit: RateLimit::default(),
+            retry_policy: RetryPolicy::default(),
+            middleware: Vec::new(),
+            cache: None,
+            cassette: None,
+            offline: false,
+            #[cfg(feature = "metrics")]
+            metrics: None,
         }
-        if let Some(platform) = platform {
-            q.params.push(Parameter::Platform(platform))
+    }
+
+    /// Root of the API, e.g. to point the client at a mirror or a local
+    /// stand-in. Defaults to `https://www.speedrun.com/api/v1/`.
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// speedrun.com asks clients to identify themselves with a descriptive
+    /// user agent. Defaults to `srcapi-plus/<version>`.
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    #[cfg(feature = "reqwest")]
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Timeout for a whole request, from connecting until the body is read.
+    #[cfg(feature = "reqwest")]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    #[cfg(feature = "reqwest")]
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Headers sent with every request.
+    pub fn default_headers(mut self, headers: HeaderMap) -> Self {
+        self.default_headers = headers;
+        self
+    }
+
+    /// Key sent in the `X-API-Key` header to authenticate as its owner.
+    pub fn api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    /// Registers `api_key` under `name`, to make requests as its owner with
+    /// [`SrcClient::with_profile`].
+    pub fn profile(mut self, name: impl Into<String>, api_key: impl Into<String>) -> Self {
+        self.profiles.insert(name.into(), api_key.into());
+        self
+    }
+
+    /// Uses an already configured `reqwest::Client`. Its own settings take
+    /// the place of the timeouts and proxy set on this builder.
+    #[cfg(feature = "reqwest")]
+    pub fn client(self, client: Client) -> Self {
+        self.transport(ReqwestTransport::new(client))
+    }
+
+    /// Sends requests through `transport` instead of reqwest, e.g. an
+    /// instrumented HTTP stack or a [`MemoryTransport`](crate::transport::MemoryTransport)
+    /// in tests. The timeouts and proxy set on this builder are not used.
+    pub fn transport(mut self, transport: impl Transport + 'static) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    pub fn rate_limit(mut self, limit: RateLimit) -> Self {
+        self.rate_limit = limit;
+        self
+    }
+
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    /// Adds a layer to the middleware chain. Layers run in the order they are
+    /// added, each wrapping the ones added after it, and all of them wrap the
+    /// client's cache, retries, cassette and rate limiting.
+    pub fn middleware(mut self, middleware: impl Middleware + 'static) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Keeps successful responses in memory so repeated calls for the same
+    /// URL don't go to the network.
+    pub fn cache(self, config: CacheConfig) -> Self {
+        self.cache_store(config, MemoryCache::default())
+    }
+
+    /// Like [`SrcClientBuilder::cache`], but keeps responses in `store`, e.g.
+    /// a [`DiskCache`](crate::cache::DiskCache) that survives restarts.
+    pub fn cache_store(mut self, config: CacheConfig, store: impl CacheStore + 'static) -> Self {
+        self.cache = Some(Cache::new(config, store));
+        self
+    }
+
+    /// Records every response into `cassette`, or serves responses from it
+    /// without touching the network, depending on its mode.
+    pub fn cassette(mut self, cassette: Cassette) -> Self {
+        self.cassette = Some(cassette);
+        self
+    }
+
+    /// Starts the client offline, see [`SrcClient::with_offline`]. Only
+    /// useful together with a cache, e.g. a persistent one.
+    pub fn offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    /// Collects usage metrics for the client, see [`Metrics`].
+    #[cfg(feature = "metrics")]
+    pub fn metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    pub fn build(self) -> Result<SrcClient, SrcError> {
+        let mut base_url = Url::parse(&self.base_url)?;
+
+        // Url::join replaces the last path segment unless the base ends in a slash
+        if !base_url.path().ends_with('/') {
+            base_url.set_path(&format!("{}/", base_url.path()));
         }
-        if let Some(region) = region {
-            q.params.push(Parameter::Region(region))
+
+        let mut default_headers = self.default_headers;
+
+        if !default_headers.contains_key(USER_AGENT) {
+            let user_agent =
+                HeaderValue::from_str(&self.user_agent).map_err(|_| QueryError::InvalidHeader {
+                    name: USER_AGENT.to_string(),
+                })?;
+
+            default_headers.insert(USER_AGENT, user_agent);
         }
-        if let Some(genre) = genre {
-            q.params.push(Parameter::Genre(genre))
+
+        let transport = match self.transport {
+            Some(transport) => transport,
+            #[cfg(not(feature = "reqwest"))]
+            None => return Err(QueryError::MissingTransport.into()),
+            #[cfg(feature = "reqwest")]
+            None => {
+                let mut builder = Client::builder();
+
+                if let Some(timeout) = self.connect_timeout {
+                    builder = builder.connect_timeout(timeout);
+                }
+                if let Some(timeout) = self.timeout {
+                    builder = builder.timeout(timeout);
+                }
+                if let Some(proxy) = self.proxy {
+                    builder = builder.proxy(proxy);
+                }
+
+                Arc::new(ReqwestTransport::new(builder.build()?))
+            }
+        };
+
+        if !self.rate_limit.is_valid() {
+            return Err(QueryError::InvalidRateLimit.into());
         }
-        if let Some(engine) = engine {
-            q.params.push(Parameter::Engine(engine))
+
+        #[cfg_attr(not(feature = "metrics"), allow(unused_mut))]
+        let mut rate_limiter = RateLimiter::new(self.rate_limit);
+        #[cfg_attr(not(feature = "metrics"), allow(unused_mut))]
+        let mut retry_policy = self.retry_policy;
+        #[cfg_attr(not(feature = "metrics"), allow(unused_mut))]
+        let mut cache = self.cache;
+
+        #[cfg(feature = "metrics")]
+        {
+            rate_limiter.metrics = self.metrics.clone();
+            retry_policy.metrics = self.metrics.clone();
+
+            if let Some(cache) = &mut cache {
+                cache.metrics = self.metrics.clone();
+            }
         }
-        if let Some(developer) = developer {
-            q.params.push(Parameter::Developer(developer))
+
+        let cache = cache.map(Arc::new);
+
+        // a replayed response never reaches the rate limiter, while every
+        // retried attempt goes through the cassette and the rate limiter again
+        let mut middleware = self.middleware;
+        if let Some(cache) = &cache {
+            middleware.push(cache.clone());
         }
-        if let Some(publisher) = publisher {
-            q.params.push(Parameter::Publisher(publisher))
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = &self.metrics {
+            middleware.push(Arc::new(metrics.clone()));
         }
-        if let Some(moderator) = moderator {
-            q.params.push(Parameter::Moderator(moderator))
+        middleware.push(Arc::new(retry_policy));
+        if let Some(cassette) = self.cassette {
+            middleware.push(Arc::new(cassette));
         }
+        let rate_limiters = Arc::new(RateLimiters::new(rate_limiter));
+        middleware.push(rate_limiters.clone());
 
-        let users_: GamesData = query(&self, &q).await?;
+        let auth = self.api_key.map(Auth::from_key);
+        let rate_limiter = rate_limiters.get(auth.as_ref().map(|auth| auth.identity.as_str()));
 
-        Ok(users_.data)
+        Ok(SrcClient {
+            transport,
+            base_url,
+            default_headers,
+            auth,
+            profiles: Arc::new(self.profiles),
+            rate_limiter,
+            rate_limiters,
+            priority: Priority::default(),
+            middleware: middleware.into(),
+            cache,
+            in_flight: Arc::default(),
+            offline: self.offline,
+            #[cfg(feature = "metrics")]
+            metrics: self.metrics,
+        })
     }
 }
 
-#[derive(Serialize, Deserialize)]
-struct UsersData {
-    data: Vec<User>,
-}
-
-#[derive(Serialize, Deserialize)]
-struct UserData {
-    data: User,
+impl Default for SrcClientBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 #[derive(Serialize, Deserialize)]
-struct GamesData {
-    data: Vec<Game>,
+struct Data<T> {
+    data: T,
 }
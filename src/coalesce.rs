@@ -0,0 +1,90 @@
+use crate::error::SrcError;
+use crate::ratelimit::Priority;
+use crate::transport::{
+    header::{HeaderName, HeaderValue},
+    HttpRequest, HttpResponse, Method, Url,
+};
+use futures::future::{BoxFuture, FutureExt, WeakShared};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Requests are identical when their method, URL and headers are, so calls
+/// made with different API keys are never merged. Their priority has to match
+/// too, or a call could end up waiting behind the rate limiter at a lower one.
+type Key = (Method, Url, Vec<(HeaderName, HeaderValue)>, Priority);
+
+type ResponseFuture = BoxFuture<'static, Result<HttpResponse, SrcError>>;
+
+/// The requests a client currently has in flight, so identical concurrent
+/// requests can share one response.
+///
+/// Only the callers hold on to a request. Once all of them have given up on
+/// it, e.g. after a timeout, it is dropped, which also gives up its place in
+/// the rate limiter's queue.
+#[derive(Default)]
+pub(crate) struct InFlight {
+    requests: Mutex<HashMap<Key, WeakShared<ResponseFuture>>>,
+}
+
+impl InFlight {
+    /// Sends `request` with `send`, unless an identical request is already in
+    /// flight, in which case its response is awaited instead. Only `GET`
+    /// requests are merged.
+    pub(crate) async fn send<F>(
+        self: &Arc<Self>,
+        request: HttpRequest,
+        send: F,
+    ) -> Result<HttpResponse, SrcError>
+    where
+        F: FnOnce(HttpRequest) -> BoxFuture<'static, Result<HttpResponse, SrcError>>,
+    {
+        if request.method != Method::GET {
+            return send(request).await;
+        }
+
+        let key: Key = (
+            request.method.clone(),
+            request.url.clone(),
+            request
+                .headers
+                .iter()
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect(),
+            request.priority,
+        );
+
+        let response = {
+            let mut requests = self.requests.lock().unwrap();
+
+            match requests.get(&key).and_then(WeakShared::upgrade) {
+                Some(response) => response,
+                None => {
+                    // forget requests every caller has given up on
+                    requests.retain(|_, response| response.upgrade().is_some());
+
+                    let in_flight = Arc::clone(self);
+                    let response = send(request);
+                    let done = key.clone();
+
+                    // the entry is removed by whichever caller drives the
+                    // request to completion, even if the first one gave up
+                    let response = async move {
+                        let result = response.await;
+                        in_flight.requests.lock().unwrap().remove(&done);
+                        result
+                    }
+                    .boxed()
+                    .shared();
+
+                    if let Some(weak) = response.downgrade() {
+                        requests.insert(key, weak);
+                    }
+
+                    response
+                }
+            }
+        };
+
+        response.await
+    }
+}
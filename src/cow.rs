@@ -0,0 +1,25 @@
+//! `deserialize_with` helpers for the borrowed models. Serde only borrows a
+//! `Cow<str>` that is the field type itself, so one nested in an `Option` or
+//! `Vec` would otherwise always allocate.
+
+use serde::{Deserialize, Deserializer};
+use std::borrow::Cow;
+
+#[derive(Deserialize)]
+struct CowStr<'a>(#[serde(borrow)] Cow<'a, str>);
+
+pub(crate) fn option<'de: 'a, 'a, D>(deserializer: D) -> Result<Option<Cow<'a, str>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(Option::<CowStr>::deserialize(deserializer)?.map(|s| s.0))
+}
+
+pub(crate) fn vec<'de: 'a, 'a, D>(deserializer: D) -> Result<Vec<Cow<'a, str>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let strings = Vec::<CowStr>::deserialize(deserializer)?;
+
+    Ok(strings.into_iter().map(|s| s.0).collect())
+}
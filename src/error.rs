@@ -0,0 +1,156 @@
+use crate::pagination::Link;
+use crate::retry::parse_retry_after;
+use crate::transport::{header::RETRY_AFTER, HttpResponse, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+
+pub use crate::query::QueryError;
+pub use crate::transport::TransportError;
+
+/// Number of bytes of a response body kept in a [`SrcError::Decode`].
+const BODY_SNIPPET_LEN: usize = 256;
+
+/// Everything that can go wrong when talking to the API.
+///
+/// Errors are cheap to clone, so a failed request shared by several callers
+/// can hand each of them the same error.
+#[derive(Error, Debug, Clone)]
+#[non_exhaustive]
+pub enum SrcError {
+    #[error("Request failed: {0}")]
+    Transport(#[from] TransportError),
+
+    #[error("Invalid url: {0}")]
+    Url(#[from] url::ParseError),
+
+    #[error("Failed to decode response at `{path}`: {source} (body: {body})")]
+    Decode {
+        path: String,
+        body: String,
+        source: Arc<serde_json::Error>,
+    },
+
+    #[error("API error ({status}): {message}")]
+    Api {
+        status: u16,
+        message: String,
+        links: Vec<Link>,
+    },
+
+    #[error("Not authorized ({status}): {message}")]
+    Unauthorized { status: u16, message: String },
+
+    #[error("Rate limited by the API")]
+    RateLimited { retry_after: Option<Duration> },
+
+    #[error(transparent)]
+    Query(#[from] QueryError),
+
+    #[error("No recorded response for {method} {url}")]
+    UnmatchedRequest { method: String, url: String },
+
+    #[error("Offline, and no cached response for {url}")]
+    Offline { url: String },
+
+    #[error("I/O error: {0}")]
+    Io(#[source] Arc<std::io::Error>),
+}
+
+impl From<std::io::Error> for SrcError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(Arc::new(error))
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl From<reqwest::Error> for SrcError {
+    fn from(error: reqwest::Error) -> Self {
+        Self::Transport(error.into())
+    }
+}
+
+impl SrcError {
+    pub(crate) fn decode(body: &str, err: serde_path_to_error::Error<serde_json::Error>) -> Self {
+        let path = err.path().to_string();
+
+        let body = match body.char_indices().nth(BODY_SNIPPET_LEN) {
+            Some((end, _)) => format!("{}...", &body[..end]),
+            None => body.to_string(),
+        };
+
+        Self::Decode {
+            path,
+            body,
+            source: Arc::new(err.into_inner()),
+        }
+    }
+
+    /// Like [`SrcError::decode`], for the item at `index` of a `data` list
+    /// that was decoded on its own.
+    pub(crate) fn decode_item(
+        index: usize,
+        body: &str,
+        err: serde_path_to_error::Error<serde_json::Error>,
+    ) -> Self {
+        match Self::decode(body, err) {
+            Self::Decode { path, body, source } => Self::Decode {
+                path: match path.as_str() {
+                    "." => format!("data[{}]", index),
+                    _ => format!("data[{}].{}", index, path),
+                },
+                body,
+                source,
+            },
+            other => other,
+        }
+    }
+
+    /// The error a response stands for, if its status is not a success. A
+    /// `304 Not Modified` is not an error.
+    pub(crate) fn from_response(response: &HttpResponse) -> Option<Self> {
+        let status = response.status;
+
+        if status == StatusCode::from_u16(420).unwrap() {
+            let retry_after = response
+                .header(RETRY_AFTER)
+                .as_deref()
+                .and_then(parse_retry_after);
+
+            return Some(Self::RateLimited { retry_after });
+        }
+
+        if status.is_success() || status == StatusCode::NOT_MODIFIED {
+            return None;
+        }
+
+        let envelope =
+            serde_json::from_slice::<ErrorEnvelope>(&response.body).unwrap_or(ErrorEnvelope {
+                status: status.as_u16(),
+                message: status.canonical_reason().unwrap_or_default().to_string(),
+                links: Vec::new(),
+            });
+
+        Some(match status {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => Self::Unauthorized {
+                status: envelope.status,
+                message: envelope.message,
+            },
+            _ => Self::Api {
+                status: envelope.status,
+                message: envelope.message,
+                links: envelope.links,
+            },
+        })
+    }
+}
+
+/// The body speedrun.com sends alongside a non-success status code.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct ErrorEnvelope {
+    pub status: u16,
+    pub message: String,
+    #[serde(default)]
+    pub links: Vec<Link>,
+}
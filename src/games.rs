@@ -2,6 +2,8 @@ use chrono::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+pub mod borrowed;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Game {
     pub id: GameId,
@@ -30,6 +32,18 @@ pub struct Game {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GameId(String);
 
+impl From<String> for GameId {
+    fn from(id: String) -> Self {
+        Self(id)
+    }
+}
+
+impl From<&str> for GameId {
+    fn from(id: &str) -> Self {
+        Self(id.to_string())
+    }
+}
+
 impl std::fmt::Display for GameId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         write!(f, "{}", self.0)
@@ -0,0 +1,249 @@
+//! Borrowed counterparts of the game models, for decoding many games without
+//! allocating a `String` for every field. Strings borrow from the response
+//! body unless they contain escapes.
+//!
+//! They save less than they might seem to: most of the time spent decoding a
+//! page used to go to tracking the path to a failing field, which is now
+//! only done once decoding has failed, for the owned models too. On the
+//! `decode` benchmark, borrowed games decode only about 10% faster than owned
+//! ones, so they are mostly worth it for large pages.
+
+use super::ModeratorRole;
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Game<'a> {
+    #[serde(borrow)]
+    pub id: GameId<'a>,
+    #[serde(borrow)]
+    pub names: Names<'a>,
+    #[serde(borrow)]
+    pub abbreviation: GameAbbreviation<'a>,
+    #[serde(borrow)]
+    pub weblink: Weblink<'a>,
+    #[deprecated = "released is a legacy value that has been superceded by release_date"]
+    pub released: i32,
+    #[serde(rename = "release-date", borrow)]
+    pub release_date: ReleaseDate<'a>,
+    #[serde(borrow)]
+    pub ruleset: Ruleset<'a>,
+    pub romhack: bool,
+    #[serde(borrow)]
+    pub gametypes: Vec<GametypeId<'a>>,
+    #[serde(borrow)]
+    pub platforms: Vec<PlatformId<'a>>,
+    #[serde(borrow)]
+    pub regions: Vec<RegionId<'a>>,
+    #[serde(borrow)]
+    pub genres: Vec<GenreId<'a>>,
+    #[serde(borrow)]
+    pub engines: Vec<EngineId<'a>>,
+    #[serde(borrow)]
+    pub developers: Vec<DeveloperId<'a>>,
+    #[serde(borrow)]
+    pub publishers: Vec<PublisherId<'a>>,
+    #[serde(borrow)]
+    pub moderators: HashMap<ModeratorId<'a>, ModeratorRole>,
+    pub created: Option<DateTime<Utc>>,
+    #[serde(borrow)]
+    pub assets: Assets<'a>,
+    #[serde(borrow)]
+    pub links: Links<'a>,
+}
+
+impl Game<'_> {
+    #[allow(deprecated)]
+    pub fn into_owned(self) -> super::Game {
+        super::Game {
+            id: self.id.into_owned(),
+            names: self.names.into_owned(),
+            abbreviation: self.abbreviation.into_owned(),
+            weblink: self.weblink.into_owned(),
+            released: self.released,
+            release_date: self.release_date.into_owned(),
+            ruleset: self.ruleset.into_owned(),
+            romhack: self.romhack,
+            gametypes: into_owned(self.gametypes, GametypeId::into_owned),
+            platforms: into_owned(self.platforms, PlatformId::into_owned),
+            regions: into_owned(self.regions, RegionId::into_owned),
+            genres: into_owned(self.genres, GenreId::into_owned),
+            engines: into_owned(self.engines, EngineId::into_owned),
+            developers: into_owned(self.developers, DeveloperId::into_owned),
+            publishers: into_owned(self.publishers, PublisherId::into_owned),
+            moderators: self
+                .moderators
+                .into_iter()
+                .map(|(id, role)| (id.into_owned(), role))
+                .collect(),
+            created: self.created,
+            assets: self.assets.into_owned(),
+            links: self.links.into_owned(),
+        }
+    }
+}
+
+fn into_owned<T, U>(items: Vec<T>, f: impl FnMut(T) -> U) -> Vec<U> {
+    items.into_iter().map(f).collect()
+}
+
+borrowed_id!(
+    GameId,
+    GameAbbreviation,
+    Weblink,
+    ReleaseDate,
+    GametypeId,
+    PlatformId,
+    RegionId,
+    GenreId,
+    EngineId,
+    DeveloperId,
+    PublisherId,
+    #[derive(PartialEq, Eq, Hash)]
+    ModeratorId,
+);
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Names<'a> {
+    #[serde(borrow)]
+    pub international: Cow<'a, str>,
+    #[serde(borrow, default, deserialize_with = "crate::cow::option")]
+    pub japanese: Option<Cow<'a, str>>,
+    #[serde(borrow, default, deserialize_with = "crate::cow::option")]
+    pub twitch: Option<Cow<'a, str>>,
+}
+
+impl Names<'_> {
+    pub fn into_owned(self) -> super::Names {
+        super::Names {
+            international: self.international.into_owned(),
+            japanese: self.japanese.map(Cow::into_owned),
+            twitch: self.twitch.map(Cow::into_owned),
+        }
+    }
+}
+
+impl std::fmt::Display for Names<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "{}", self.international)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Ruleset<'a> {
+    #[serde(rename = "show-milliseconds")]
+    pub show_milliseconds: bool,
+    #[serde(rename = "require-verification")]
+    pub require_verification: bool,
+    #[serde(rename = "require-video")]
+    pub require_video: bool,
+    #[serde(rename = "run-times", borrow, deserialize_with = "crate::cow::vec")]
+    pub run_times: Vec<Cow<'a, str>>,
+    #[serde(rename = "default-time", borrow)]
+    pub default_time: Cow<'a, str>,
+    #[serde(rename = "emulators-allowed")]
+    pub emulators_allowed: bool,
+}
+
+impl Ruleset<'_> {
+    pub fn into_owned(self) -> super::Ruleset {
+        super::Ruleset {
+            show_milliseconds: self.show_milliseconds,
+            require_verification: self.require_verification,
+            require_video: self.require_video,
+            run_times: into_owned(self.run_times, Cow::into_owned),
+            default_time: self.default_time.into_owned(),
+            emulators_allowed: self.emulators_allowed,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Assets<'a> {
+    #[serde(borrow)]
+    pub logo: Option<Asset<'a>>,
+    #[serde(rename = "cover-tiny", borrow)]
+    pub cover_tiny: Option<Asset<'a>>,
+    #[serde(rename = "cover-small", borrow)]
+    pub cover_small: Option<Asset<'a>>,
+    #[serde(rename = "cover-medium", borrow)]
+    pub cover_medium: Option<Asset<'a>>,
+    #[serde(rename = "cover-large", borrow)]
+    pub cover_large: Option<Asset<'a>>,
+    #[serde(borrow)]
+    pub icon: Option<Asset<'a>>,
+    #[serde(rename = "trophy-1st", borrow)]
+    pub trophy_1st: Option<Asset<'a>>,
+    #[serde(rename = "trophy-2nd", borrow)]
+    pub trophy_2nd: Option<Asset<'a>>,
+    #[serde(rename = "trophy-3rd", borrow)]
+    pub trophy_3rd: Option<Asset<'a>>,
+    #[serde(rename = "trophy-4th", borrow)]
+    pub trophy_4th: Option<Asset<'a>>,
+    #[serde(borrow)]
+    pub foreground: Option<Asset<'a>>,
+}
+
+impl Assets<'_> {
+    pub fn into_owned(self) -> super::Assets {
+        super::Assets {
+            logo: self.logo.map(Asset::into_owned),
+            cover_tiny: self.cover_tiny.map(Asset::into_owned),
+            cover_small: self.cover_small.map(Asset::into_owned),
+            cover_medium: self.cover_medium.map(Asset::into_owned),
+            cover_large: self.cover_large.map(Asset::into_owned),
+            icon: self.icon.map(Asset::into_owned),
+            trophy_1st: self.trophy_1st.map(Asset::into_owned),
+            trophy_2nd: self.trophy_2nd.map(Asset::into_owned),
+            trophy_3rd: self.trophy_3rd.map(Asset::into_owned),
+            trophy_4th: self.trophy_4th.map(Asset::into_owned),
+            foreground: self.foreground.map(Asset::into_owned),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Asset<'a> {
+    #[serde(borrow, default, deserialize_with = "crate::cow::option")]
+    uri: Option<Cow<'a, str>>,
+    width: Option<i32>,
+    height: Option<i32>,
+}
+
+impl Asset<'_> {
+    pub fn into_owned(self) -> super::Asset {
+        super::Asset {
+            uri: self.uri.map(Cow::into_owned),
+            width: self.width,
+            height: self.height,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Links<'a>(#[serde(borrow)] Vec<Link<'a>>);
+
+impl Links<'_> {
+    pub fn into_owned(self) -> super::Links {
+        super::Links(into_owned(self.0, Link::into_owned))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Link<'a> {
+    #[serde(borrow)]
+    pub rel: Cow<'a, str>,
+    #[serde(borrow)]
+    pub uri: Cow<'a, str>,
+}
+
+impl Link<'_> {
+    pub fn into_owned(self) -> super::Link {
+        super::Link {
+            rel: self.rel.into_owned(),
+            uri: self.uri.into_owned(),
+        }
+    }
+}
@@ -0,0 +1,326 @@
+use crate::{
+    client::SrcClient,
+    error::SrcError,
+    middleware::Next,
+    pagination::Pagination,
+    query::{self, prepare, QueryKind},
+    transport::{header::HeaderMap, BodyStream, Method, Url},
+};
+use futures::stream::{self, BoxStream, Stream, StreamExt};
+use serde::{de::DeserializeOwned, Deserialize};
+use std::collections::VecDeque;
+
+/// What a list response yields as it is decoded.
+pub(crate) enum PageEvent<T> {
+    Item(T),
+    /// The pagination block, once the whole body has been read.
+    End(Pagination),
+}
+
+#[derive(Deserialize)]
+struct Skeleton {
+    pagination: Pagination,
+}
+
+/// Fetches a list page and decodes its `data` items one by one while the body
+/// downloads, so only a single item is held in memory at a time.
+///
+/// The request goes down the middleware chain like any other, but isn't
+/// merged with identical ones in flight, as its body can only be read once.
+pub(crate) fn fetch_page<'a, T>(
+    client: &'a SrcClient,
+    kind: QueryKind,
+    url: Url,
+) -> BoxStream<'a, Result<PageEvent<T>, SrcError>>
+where
+    T: DeserializeOwned + Send + 'a,
+{
+    let body = async move {
+        let request = prepare(client, kind, Method::GET, url, HeaderMap::new())?;
+
+        let response = Next::new(&client.middleware, &*client.transport)
+            .run_streaming(request)
+            .await?;
+
+        // error bodies are small, read them whole to decode the envelope
+        if !response.status.is_success() {
+            let response = response.collect().await?;
+
+            return Err(SrcError::from_response(&response).unwrap_or(SrcError::Api {
+                status: response.status.as_u16(),
+                message: String::new(),
+                links: Vec::new(),
+            }));
+        }
+
+        Ok(response.body)
+    };
+
+    stream::once(body)
+        .flat_map(|body| match body {
+            Ok(body) => decode(body).left_stream(),
+            Err(e) => stream::once(async move { Err(e) }).right_stream(),
+        })
+        .boxed()
+}
+
+struct State {
+    body: Option<BodyStream>,
+    decoder: ListDecoder,
+    items: VecDeque<Vec<u8>>,
+    index: usize,
+}
+
+fn decode<T>(body: BodyStream) -> impl Stream<Item = Result<PageEvent<T>, SrcError>>
+where
+    T: DeserializeOwned,
+{
+    let state = State {
+        body: Some(body),
+        decoder: ListDecoder::default(),
+        items: VecDeque::new(),
+        index: 0,
+    };
+
+    stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(item) = state.items.pop_front() {
+                let index = state.index;
+                state.index += 1;
+
+                // only track the path once decoding has failed, as in query::decode
+                let result = serde_json::from_slice(&item)
+                    .or_else(|_| {
+                        let de = &mut serde_json::Deserializer::from_slice(&item);
+
+                        serde_path_to_error::deserialize(de).map_err(|e| {
+                            SrcError::decode_item(index, &String::from_utf8_lossy(&item), e)
+                        })
+                    })
+                    .map(PageEvent::Item);
+
+                return Some((result, state));
+            }
+
+            let body = state.body.as_mut()?;
+
+            match body.next().await {
+                Some(Ok(chunk)) => state.items.extend(state.decoder.push(&chunk)),
+                Some(Err(e)) => {
+                    state.body = None;
+                    return Some((Err(e), state));
+                }
+                None => {
+                    state.body = None;
+
+                    let skeleton = std::mem::take(&mut state.decoder.skeleton);
+
+                    let result = query::decode::<Skeleton>(&skeleton)
+                        .map(|skeleton| PageEvent::End(skeleton.pagination));
+
+                    return Some((result, state));
+                }
+            }
+        }
+    })
+}
+
+/// Splits the items of the top-level `data` array out of a JSON body fed in
+/// chunks. Everything else is kept as a skeleton with an empty `data` array,
+/// for decoding the rest of the response once the body is done.
+#[derive(Default)]
+struct ListDecoder {
+    skeleton: Vec<u8>,
+    item: Vec<u8>,
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+    in_data: bool,
+}
+
+impl ListDecoder {
+    /// Feeds a chunk of the body, returning the items it completed.
+    fn push(&mut self, chunk: &[u8]) -> Vec<Vec<u8>> {
+        let mut items = Vec::new();
+
+        for &byte in chunk {
+            let between_items = self.in_data && self.depth == 2 && !self.in_string;
+
+            if between_items && matches!(byte, b',' | b']') {
+                let len = trim_end(&self.item).len();
+                self.item.truncate(len);
+
+                if !self.item.is_empty() {
+                    items.push(std::mem::take(&mut self.item));
+                }
+
+                if byte == b']' {
+                    self.in_data = false;
+                    self.depth -= 1;
+                    self.skeleton.push(byte);
+                }
+
+                continue;
+            }
+
+            if between_items && byte.is_ascii_whitespace() && self.item.is_empty() {
+                continue;
+            }
+
+            if !self.in_data && !self.in_string && byte == b'[' && self.depth == 1 {
+                let at_data = self.after_data_key();
+
+                self.skeleton.push(byte);
+                self.depth += 1;
+                self.in_data = at_data;
+
+                continue;
+            }
+
+            self.track(byte);
+
+            match self.in_data {
+                true => self.item.push(byte),
+                false => self.skeleton.push(byte),
+            }
+        }
+
+        items
+    }
+
+    /// Updates the nesting and string state for `byte`.
+    fn track(&mut self, byte: u8) {
+        if self.in_string {
+            match (self.escaped, byte) {
+                (true, _) => self.escaped = false,
+                (false, b'\\') => self.escaped = true,
+                (false, b'"') => self.in_string = false,
+                _ => {}
+            }
+
+            return;
+        }
+
+        match byte {
+            b'"' => self.in_string = true,
+            b'{' | b'[' => self.depth += 1,
+            b'}' | b']' => self.depth = self.depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+
+    /// Whether the skeleton so far ends in the `"data":` key.
+    fn after_data_key(&self) -> bool {
+        let rest = trim_end(&self.skeleton);
+
+        match rest.strip_suffix(b":") {
+            Some(rest) => trim_end(rest).ends_with(b"\"data\""),
+            None => false,
+        }
+    }
+}
+
+fn trim_end(bytes: &[u8]) -> &[u8] {
+    let end = bytes
+        .iter()
+        .rposition(|b| !b.is_ascii_whitespace())
+        .map_or(0, |i| i + 1);
+
+    &bytes[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feeds `body` to a decoder `chunk` bytes at a time, returning the items
+    /// and the skeleton.
+    fn split(body: &str, chunk: usize) -> (Vec<String>, String) {
+        let mut decoder = ListDecoder::default();
+        let mut items = Vec::new();
+
+        for chunk in body.as_bytes().chunks(chunk) {
+            items.extend(decoder.push(chunk));
+        }
+
+        let items = items
+            .into_iter()
+            .map(|item| String::from_utf8(item).unwrap())
+            .collect();
+
+        (items, String::from_utf8(decoder.skeleton).unwrap())
+    }
+
+    const PAGINATION: &str = r#"{"offset":0,"max":2,"size":2,"links":[]}"#;
+
+    #[test]
+    fn splits_items_and_keeps_the_skeleton() {
+        let body = format!(
+            r#"{{"data": [ {{"id":1}} , {{"id":2}} ], "pagination":{}}}"#,
+            PAGINATION
+        );
+
+        let (items, skeleton) = split(&body, body.len());
+
+        assert_eq!(items, [r#"{"id":1}"#, r#"{"id":2}"#]);
+        let skeleton: Skeleton = serde_json::from_str(&skeleton).unwrap();
+        assert_eq!(skeleton.pagination.size, 2);
+    }
+
+    #[test]
+    fn ignores_brackets_and_escaped_quotes_in_strings() {
+        let body = r#"{"data":[{"name":"a \"],[\" b"},{"name":"\\"}],"pagination":{}}"#;
+
+        let (items, _) = split(body, 1);
+
+        assert_eq!(items, [r#"{"name":"a \"],[\" b"}"#, r#"{"name":"\\"}"#]);
+    }
+
+    #[test]
+    fn only_splits_the_top_level_data_key() {
+        let body = r#"{"meta":{"data":[1,2]},"data":[{"data":[3,4]},[5,6]],"pagination":{}}"#;
+
+        let (items, skeleton) = split(body, 1);
+
+        assert_eq!(items, [r#"{"data":[3,4]}"#, "[5,6]"]);
+        assert_eq!(
+            skeleton,
+            r#"{"meta":{"data":[1,2]},"data":[],"pagination":{}}"#
+        );
+    }
+
+    #[test]
+    fn same_result_for_any_chunk_size() {
+        let body = format!(
+            r#"{{"data":[{{"id":"x,y"}},{{"id":"]"}},{{"id":"z"}}],"pagination":{}}}"#,
+            PAGINATION
+        );
+
+        let whole = split(&body, body.len());
+
+        for chunk in 1..body.len() {
+            assert_eq!(split(&body, chunk), whole, "chunk size {}", chunk);
+        }
+    }
+
+    #[test]
+    fn accepts_pagination_before_data() {
+        let body = format!(r#"{{"pagination":{},"data":[{{"id":1}}]}}"#, PAGINATION);
+
+        let (items, skeleton) = split(&body, 1);
+
+        assert_eq!(items, [r#"{"id":1}"#]);
+        let skeleton: Skeleton = serde_json::from_str(&skeleton).unwrap();
+        assert_eq!(skeleton.pagination.max, 2);
+    }
+
+    #[test]
+    fn empty_data_yields_no_items() {
+        let body = format!(r#"{{"data":[ ],"pagination":{}}}"#, PAGINATION);
+
+        let (items, skeleton) = split(&body, 1);
+
+        assert!(items.is_empty());
+        assert!(serde_json::from_str::<Skeleton>(&skeleton).is_ok());
+    }
+}
@@ -1,6 +1,27 @@
+#[macro_use]
+mod macros;
+
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod cache;
+pub mod cassette;
 pub mod client;
+mod coalesce;
+mod cow;
+pub mod error;
 pub mod games;
-mod query;
+mod incremental;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod middleware;
+pub mod pagination;
+pub mod query;
+pub mod ratelimit;
+pub mod resource;
+pub mod response;
+pub mod retry;
+pub mod runs;
+pub mod transport;
 pub mod users;
 
 const BASE_URL: &str = "https://www.speedrun.com/api/v1/";
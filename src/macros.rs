@@ -0,0 +1,23 @@
+/// Declares borrowed string newtypes for a model's `borrowed` module, each
+/// mirroring the owned newtype of the same name in the parent module.
+macro_rules! borrowed_id {
+    ($($(#[$attr:meta])* $name:ident),* $(,)?) => {
+        $(
+            $(#[$attr])*
+            #[derive(Debug, serde::Serialize, serde::Deserialize)]
+            pub struct $name<'a>(#[serde(borrow)] std::borrow::Cow<'a, str>);
+
+            impl $name<'_> {
+                pub fn into_owned(self) -> super::$name {
+                    super::$name(self.0.into_owned())
+                }
+            }
+
+            impl std::fmt::Display for $name<'_> {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+                    write!(f, "{}", self.0)
+                }
+            }
+        )*
+    };
+}
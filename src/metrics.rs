@@ -0,0 +1,247 @@
+use crate::error::SrcError;
+use crate::middleware::{Middleware, Next};
+use crate::query::QueryKind;
+use crate::transport::{HttpRequest, HttpResponse, StatusCode, StreamingResponse};
+use futures::future::BoxFuture;
+use prometheus::{
+    Encoder, Histogram, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, Opts, Registry,
+    TextEncoder,
+};
+use std::borrow::Cow;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Usage metrics of a [`SrcClient`](crate::client::SrcClient), shared by all
+/// of its clones.
+///
+/// The metrics are kept in a registry of their own, which [`Metrics::render`]
+/// exports in the Prometheus text format. They can also be added to an
+/// application's registry with [`Metrics::register`].
+///
+/// | Metric | Labels |
+/// |---|---|
+/// | `srcapi_requests_total` | `endpoint` (e.g. `games` or `games/{id}`), `status` (`error` without a response) |
+/// | `srcapi_request_duration_seconds` | `endpoint` |
+/// | `srcapi_cache_lookups_total` | `endpoint`, `result` (`hit`, `miss` or `revalidated`) |
+/// | `srcapi_retries_total` | `reason` (`rate_limited`, `server_error` or `transport`) |
+/// | `srcapi_rate_limit_wait_seconds` | |
+/// | `srcapi_rate_limit_remaining` | |
+#[derive(Clone)]
+pub struct Metrics {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    registry: Registry,
+    requests: IntCounterVec,
+    duration: HistogramVec,
+    cache_lookups: IntCounterVec,
+    retries: IntCounterVec,
+    rate_limit_wait: Histogram,
+    rate_limit_remaining: IntGauge,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let requests = IntCounterVec::new(
+            Opts::new("srcapi_requests_total", "API calls by endpoint and status"),
+            &["endpoint", "status"],
+        )
+        .unwrap();
+
+        let duration = HistogramVec::new(
+            HistogramOpts::new(
+                "srcapi_request_duration_seconds",
+                "Duration of API calls, including retries",
+            ),
+            &["endpoint"],
+        )
+        .unwrap();
+
+        let cache_lookups = IntCounterVec::new(
+            Opts::new("srcapi_cache_lookups_total", "Response cache lookups"),
+            &["endpoint", "result"],
+        )
+        .unwrap();
+
+        let retries = IntCounterVec::new(
+            Opts::new("srcapi_retries_total", "Retried requests by reason"),
+            &["reason"],
+        )
+        .unwrap();
+
+        let rate_limit_wait = Histogram::with_opts(
+            HistogramOpts::new(
+                "srcapi_rate_limit_wait_seconds",
+                "Time spent waiting for the client-side rate limiter",
+            )
+            .buckets(vec![0.001, 0.1, 0.5, 1.0, 5.0, 15.0, 30.0, 60.0]),
+        )
+        .unwrap();
+
+        let rate_limit_remaining = IntGauge::new(
+            "srcapi_rate_limit_remaining",
+            "Requests left in the client-side rate limiter",
+        )
+        .unwrap();
+
+        let inner = Inner {
+            registry: Registry::new(),
+            requests,
+            duration,
+            cache_lookups,
+            retries,
+            rate_limit_wait,
+            rate_limit_remaining,
+        };
+
+        inner
+            .register(&inner.registry)
+            .expect("metric names are unique");
+
+        Self {
+            inner: Arc::new(inner),
+        }
+    }
+
+    /// Adds the metrics to `registry`, e.g. the one an application already
+    /// serves. They keep being exported by [`Metrics::render`] as well.
+    pub fn register(&self, registry: &Registry) -> prometheus::Result<()> {
+        self.inner.register(registry)
+    }
+
+    /// The metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let mut buffer = Vec::new();
+
+        TextEncoder::new()
+            .encode(&self.inner.registry.gather(), &mut buffer)
+            .expect("text encoding does not fail");
+
+        String::from_utf8(buffer).expect("text encoding is UTF-8")
+    }
+
+    pub(crate) fn observe_request(
+        &self,
+        kind: QueryKind,
+        status: Option<StatusCode>,
+        duration: Duration,
+    ) {
+        let status = status.map(|s| s.as_u16().to_string());
+        let status = status.as_deref().unwrap_or("error");
+
+        self.inner
+            .requests
+            .with_label_values(&[&endpoint(kind), status])
+            .inc();
+        self.inner
+            .duration
+            .with_label_values(&[&endpoint(kind)])
+            .observe(duration.as_secs_f64());
+    }
+
+    pub(crate) fn observe_cache(&self, kind: QueryKind, result: &str) {
+        self.inner
+            .cache_lookups
+            .with_label_values(&[&endpoint(kind), result])
+            .inc();
+    }
+
+    pub(crate) fn observe_retry(&self, error: &SrcError) {
+        let reason = match error {
+            SrcError::RateLimited { .. } => "rate_limited",
+            SrcError::Api { .. } => "server_error",
+            SrcError::Transport(_) => "transport",
+            _ => "other",
+        };
+
+        self.inner.retries.with_label_values(&[reason]).inc();
+    }
+
+    pub(crate) fn observe_rate_limit(&self, wait: Duration, remaining: u32) {
+        self.inner.rate_limit_wait.observe(wait.as_secs_f64());
+        self.inner.rate_limit_remaining.set(remaining as i64);
+    }
+}
+
+/// Counts and times every request that gets past the cache, retries
+/// included. Streamed responses are timed up to their headers, as the body is
+/// read by the caller.
+impl Middleware for Metrics {
+    fn handle<'a>(
+        &'a self,
+        request: HttpRequest,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<HttpResponse, SrcError>> {
+        Box::pin(async move {
+            let kind = request.kind;
+            let start = Instant::now();
+
+            let result = next.run(request).await;
+
+            let status = result.as_ref().ok().map(|response| response.status);
+            self.observe_request(kind, status, start.elapsed());
+
+            result
+        })
+    }
+
+    fn handle_streaming<'a>(
+        &'a self,
+        request: HttpRequest,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<StreamingResponse, SrcError>> {
+        Box::pin(async move {
+            let kind = request.kind;
+            let start = Instant::now();
+
+            let result = next.run_streaming(request).await;
+
+            let status = result.as_ref().ok().map(|response| response.status);
+            self.observe_request(kind, status, start.elapsed());
+
+            result
+        })
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Metrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Metrics").finish_non_exhaustive()
+    }
+}
+
+impl Inner {
+    fn register(&self, registry: &Registry) -> prometheus::Result<()> {
+        registry.register(Box::new(self.requests.clone()))?;
+        registry.register(Box::new(self.duration.clone()))?;
+        registry.register(Box::new(self.cache_lookups.clone()))?;
+        registry.register(Box::new(self.retries.clone()))?;
+        registry.register(Box::new(self.rate_limit_wait.clone()))?;
+        registry.register(Box::new(self.rate_limit_remaining.clone()))?;
+
+        Ok(())
+    }
+}
+
+/// The endpoint label of `kind`. Gets of a single item are labelled with
+/// the path pattern rather than the ID, to keep the number of series small.
+fn endpoint(kind: QueryKind) -> Cow<'static, str> {
+    match kind {
+        QueryKind::User => "user".into(),
+        QueryKind::Users => "users".into(),
+        QueryKind::UserPBs => "user_pbs".into(),
+        QueryKind::Profile => "profile".into(),
+        QueryKind::Games => "games".into(),
+        QueryKind::Get(path) => format!("{}/{{id}}", path).into(),
+        QueryKind::List(path) => path.into(),
+        QueryKind::Raw => "raw".into(),
+    }
+}
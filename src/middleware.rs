@@ -0,0 +1,193 @@
+use crate::{
+    error::SrcError,
+    transport::{HttpRequest, HttpResponse, StreamingResponse, Transport},
+};
+use futures::future::BoxFuture;
+use std::sync::Arc;
+
+/// A layer around every request a [`SrcClient`](crate::client::SrcClient)
+/// sends.
+///
+/// A middleware receives the outgoing request and the rest of the chain as
+/// `next`. It can change the request before passing it on, change the
+/// response on its way back, call `next` several times (as retries do), or
+/// not call it at all and answer the request itself.
+///
+/// Middleware registered on the builder runs in registration order, outermost
+/// first, before the client's own layers: the [`Cache`](crate::cache::Cache),
+/// usage metrics when enabled, the
+/// [`RetryPolicy`](crate::retry::RetryPolicy), a
+/// [`Cassette`](crate::cassette::Cassette) and the
+/// [`RateLimiter`](crate::ratelimit::RateLimiter), in that order. All of them
+/// are middleware like any other.
+///
+/// Incremental pages go through the chain with
+/// [`handle_streaming`](Middleware::handle_streaming), so that their body can
+/// be decoded as it arrives.
+pub trait Middleware: Send + Sync {
+    fn handle<'a>(
+        &'a self,
+        request: HttpRequest,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<HttpResponse, SrcError>>;
+
+    /// Handles a request whose response body is read by the caller as it
+    /// arrives.
+    ///
+    /// By default the request goes through [`handle`](Middleware::handle),
+    /// so the rest of the chain reads the whole body before it is passed on.
+    /// Layers that do not need the body should override this and call
+    /// [`Next::run_streaming`] instead.
+    fn handle_streaming<'a>(
+        &'a self,
+        request: HttpRequest,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<StreamingResponse, SrcError>> {
+        Box::pin(async move {
+            self.handle(request, next)
+                .await
+                .map(StreamingResponse::from)
+        })
+    }
+}
+
+impl<M> Middleware for Arc<M>
+where
+    M: Middleware + ?Sized,
+{
+    fn handle<'a>(
+        &'a self,
+        request: HttpRequest,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<HttpResponse, SrcError>> {
+        (**self).handle(request, next)
+    }
+
+    fn handle_streaming<'a>(
+        &'a self,
+        request: HttpRequest,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<StreamingResponse, SrcError>> {
+        (**self).handle_streaming(request, next)
+    }
+}
+
+/// The remainder of a middleware chain, ending in the transport.
+#[derive(Clone, Copy)]
+pub struct Next<'a> {
+    middleware: &'a [Arc<dyn Middleware>],
+    transport: &'a dyn Transport,
+}
+
+impl<'a> Next<'a> {
+    pub(crate) fn new(middleware: &'a [Arc<dyn Middleware>], transport: &'a dyn Transport) -> Self {
+        Self {
+            middleware,
+            transport,
+        }
+    }
+
+    pub fn run(self, request: HttpRequest) -> BoxFuture<'a, Result<HttpResponse, SrcError>> {
+        match self.middleware.split_first() {
+            Some((first, rest)) => first.handle(request, Next::new(rest, self.transport)),
+            #[cfg(feature = "tracing")]
+            None => send_traced(self.transport, request),
+            #[cfg(not(feature = "tracing"))]
+            None => self.transport.send(request),
+        }
+    }
+
+    /// Like [`run`](Next::run), but leaves the response body to be read as it
+    /// arrives.
+    pub fn run_streaming(
+        self,
+        request: HttpRequest,
+    ) -> BoxFuture<'a, Result<StreamingResponse, SrcError>> {
+        match self.middleware.split_first() {
+            Some((first, rest)) => first.handle_streaming(request, Next::new(rest, self.transport)),
+            #[cfg(feature = "tracing")]
+            None => send_streaming_traced(self.transport, request),
+            #[cfg(not(feature = "tracing"))]
+            None => self.transport.send_streaming(request),
+        }
+    }
+}
+
+/// Sends a request in its own span. Only the method and URL of the request
+/// are recorded, never its headers.
+#[cfg(feature = "tracing")]
+fn send_traced(
+    transport: &dyn Transport,
+    request: HttpRequest,
+) -> BoxFuture<'_, Result<HttpResponse, SrcError>> {
+    use std::time::Instant;
+    use tracing::{field::Empty, Instrument, Span};
+
+    let span = tracing::debug_span!(
+        "http_attempt",
+        method = %request.method,
+        url = %request.url,
+        status = Empty,
+        latency_ms = Empty,
+        bytes = Empty,
+    );
+
+    let attempt = async move {
+        let start = Instant::now();
+        let result = transport.send(request).await;
+
+        let span = Span::current();
+        span.record("latency_ms", start.elapsed().as_millis() as u64);
+
+        match &result {
+            Ok(response) => {
+                span.record("status", response.status.as_u16());
+                span.record("bytes", response.body.len());
+            }
+            Err(error) => tracing::debug!(%error, "request failed"),
+        }
+
+        result
+    };
+
+    Box::pin(attempt.instrument(span))
+}
+
+/// Like [`send_traced`], but the span only covers the time to the headers, as
+/// the body is read by the caller.
+#[cfg(feature = "tracing")]
+fn send_streaming_traced(
+    transport: &dyn Transport,
+    request: HttpRequest,
+) -> BoxFuture<'_, Result<StreamingResponse, SrcError>> {
+    use std::time::Instant;
+    use tracing::{field::Empty, Instrument, Span};
+
+    let span = tracing::debug_span!(
+        "http_attempt",
+        method = %request.method,
+        url = %request.url,
+        status = Empty,
+        latency_ms = Empty,
+        streaming = true,
+    );
+
+    let attempt = async move {
+        let start = Instant::now();
+        let result = transport.send_streaming(request).await;
+
+        let span = Span::current();
+        span.record("latency_ms", start.elapsed().as_millis() as u64);
+
+        match &result {
+            Ok(response) => {
+                span.record("status", response.status.as_u16());
+            }
+            Err(error) => tracing::debug!(%error, "request failed"),
+        }
+
+        result
+    };
+
+    Box::pin(attempt.instrument(span))
+}
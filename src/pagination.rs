@@ -0,0 +1,201 @@
+use crate::transport::Url;
+use crate::{
+    client::SrcClient,
+    error::{QueryError, SrcError},
+    incremental::{fetch_page, PageEvent},
+    query::{build_url, fetch, Parameter, QueryData, QueryKind},
+    BASE_URL,
+};
+use futures::stream::{self, BoxStream, Stream, StreamExt};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+/// Controls where a paginated stream starts and how much it fetches.
+#[derive(Debug, Clone, Default)]
+pub struct Paging {
+    /// Offset of the first item, e.g. to resume an interrupted crawl.
+    pub offset: Option<u32>,
+    /// Number of items requested per page (the API allows at most 200).
+    pub max: Option<u32>,
+    /// Total number of items to yield before the stream ends.
+    pub limit: Option<usize>,
+    /// Decode each page item by item while it downloads instead of reading
+    /// it whole first, so memory use doesn't grow with the page size. Pages
+    /// still go through the middleware chain, see
+    /// [`Middleware::handle_streaming`](crate::middleware::Middleware::handle_streaming),
+    /// but are not merged with identical calls in flight nor stored in the
+    /// cache. Ignored while the client is offline.
+    pub incremental: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub data: Vec<T>,
+    pub pagination: Pagination,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Pagination {
+    pub offset: u32,
+    pub max: u32,
+    pub size: u32,
+    pub links: Vec<Link>,
+}
+
+impl Pagination {
+    pub fn next(&self) -> Option<&str> {
+        self.links
+            .iter()
+            .find(|link| link.rel == "next")
+            .map(|link| link.uri.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Link {
+    pub rel: String,
+    pub uri: String,
+}
+
+struct State<'a, T> {
+    page: Option<BoxStream<'a, Result<PageEvent<T>, SrcError>>>,
+    next: Option<Url>,
+    error: Option<SrcError>,
+    remaining: Option<usize>,
+}
+
+/// Yields every item matched by `query`, following `next` links until the
+/// API runs out of pages or `paging.limit` items have been produced.
+pub(crate) fn paginate<'a, T>(
+    client: &'a SrcClient,
+    mut query: QueryData,
+    paging: Paging,
+) -> impl Stream<Item = Result<T, SrcError>> + 'a
+where
+    T: DeserializeOwned + Send + 'a,
+{
+    if let Some(offset) = paging.offset {
+        query.params.push(Parameter::Offset(offset));
+    }
+    if let Some(max) = paging.max {
+        query.params.push(Parameter::Max(max));
+    }
+
+    let kind = query.query_type.kind();
+
+    let (next, error) = match build_url(&client.base_url, &query) {
+        Ok(url) => (Some(url), None),
+        Err(e) => (None, Some(e)),
+    };
+
+    let state = State {
+        page: None,
+        next,
+        error,
+        remaining: paging.limit,
+    };
+
+    stream::unfold(state, move |mut state| async move {
+        loop {
+            if let Some(e) = state.error.take() {
+                return Some((Err(e), state));
+            }
+
+            if state.remaining == Some(0) {
+                return None;
+            }
+
+            if let Some(page) = &mut state.page {
+                match page.next().await {
+                    Some(Ok(PageEvent::Item(item))) => {
+                        state.remaining = state.remaining.map(|r| r - 1);
+
+                        return Some((Ok(item), state));
+                    }
+                    Some(Ok(PageEvent::End(pagination))) => {
+                        match pagination
+                            .next()
+                            .map(|next| next_url(client, next))
+                            .transpose()
+                        {
+                            Ok(next) => state.next = next,
+                            Err(e) => state.error = Some(e),
+                        }
+                    }
+                    Some(Err(e)) => {
+                        state.page = None;
+
+                        return Some((Err(e), state));
+                    }
+                    None => state.page = None,
+                }
+
+                continue;
+            }
+
+            let url = state.next.take()?;
+
+            // incremental pages bypass the cache, which is all there is offline
+            state.page = Some(match paging.incremental && !client.offline {
+                true => fetch_page(client, kind, url),
+                false => buffered_page(client, kind, url),
+            });
+        }
+    })
+}
+
+/// Resolves a `next` link from a response against the client's base URL.
+///
+/// The API names its own host in these links, so a link to it is moved onto
+/// the base URL of a mirror or stand-in. A link anywhere else is refused, as
+/// the API key would go along with it.
+fn next_url(client: &SrcClient, next: &str) -> Result<Url, SrcError> {
+    let base = client.base_url.as_str();
+
+    let url = match next
+        .strip_prefix(base)
+        .or_else(|| next.strip_prefix(BASE_URL))
+    {
+        Some(rest) => client.base_url.join(rest)?,
+        None => Url::parse(next)?,
+    };
+
+    if !url.as_str().starts_with(base) {
+        return Err(QueryError::InvalidPath {
+            path: next.to_string(),
+        }
+        .into());
+    }
+
+    Ok(url)
+}
+
+/// Fetches a whole page through the usual request path and replays it as
+/// events.
+fn buffered_page<'a, T>(
+    client: &'a SrcClient,
+    kind: QueryKind,
+    url: Url,
+) -> BoxStream<'a, Result<PageEvent<T>, SrcError>>
+where
+    T: DeserializeOwned + Send + 'a,
+{
+    stream::once(fetch::<Page<T>>(client, kind, url))
+        .flat_map(|page| {
+            let events: Vec<_> = match page {
+                Ok(page) => {
+                    let page = page.into_inner();
+
+                    page.data
+                        .into_iter()
+                        .map(PageEvent::Item)
+                        .chain([PageEvent::End(page.pagination)])
+                        .map(Ok)
+                        .collect()
+                }
+                Err(e) => vec![Err(e)],
+            };
+
+            stream::iter(events)
+        })
+        .boxed()
+}
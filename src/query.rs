@@ -1,24 +1,63 @@
+use crate::cache;
 use crate::client::SrcClient;
-use crate::BASE_URL;
-use reqwest::Url;
-use serde::de::DeserializeOwned;
-use std::error::Error;
+use crate::error::SrcError;
+use crate::middleware::Next;
+use crate::resource::ListQuery;
+use crate::response::{Freshness, Response};
+use crate::transport::{
+    header::{HeaderMap, HeaderValue},
+    Method, Url,
+};
+use crate::transport::{HttpRequest, HttpResponse};
+use bytes::Bytes;
+use serde::de::{Deserialize, DeserializeOwned};
 use thiserror::Error;
 
-pub async fn query<T>(client: &SrcClient, query: &QueryData) -> Result<T, Box<dyn Error>>
+const API_KEY_HEADER: &str = "X-API-Key";
+
+/// Sends `query` through the client's pipeline and decodes the response
+/// into `T`.
+pub async fn query<T>(client: &SrcClient, query: &QueryData) -> Result<Response<T>, SrcError>
 where
     T: DeserializeOwned,
 {
-    let mut url = Url::parse(BASE_URL)?;
+    let url = build_url(&client.base_url, query)?;
+
+    fetch(client, query.query_type.kind(), url).await
+}
+
+/// Like [`query`], but returns the undecoded body, e.g. for decoding it into
+/// the borrowed models with [`Response::decode`].
+pub async fn query_body(
+    client: &SrcClient,
+    query: &QueryData,
+) -> Result<Response<Bytes>, SrcError> {
+    let url = build_url(&client.base_url, query)?;
+
+    fetch_body(client, query.query_type.kind(), url).await
+}
 
-    url = url.join(&match query.query_type {
+/// The URL `query` is sent to, relative to `base_url`. Fails if the query
+/// has the wrong parameters for its endpoint.
+pub fn build_url(base_url: &Url, query: &QueryData) -> Result<Url, SrcError> {
+    let mut url = base_url.join(&match query.query_type {
         QueryType::User { ref id } => format!("users/{}", id),
         QueryType::Users => "users".to_string(),
-        // QueryType::UserPBs { ref id } => format!("users/{}/personal-bests", id),
-        QueryType::UserPBs { .. } => todo!(),
+        QueryType::UserPBs { ref id } => format!("users/{}/personal-bests", id),
         QueryType::Games => "games".to_string(),
+        QueryType::Profile => "profile".to_string(),
+        QueryType::Get { path, ref id } => format!("{}/{}", path, id),
+        QueryType::List { path } => path.to_string(),
+        QueryType::Raw { ref path } => path.trim_start_matches('/').to_string(),
     })?;
 
+    // a raw path must not leave the API, or the API key would go with it
+    if !url.as_str().starts_with(base_url.as_str()) {
+        if let QueryType::Raw { path } = &query.query_type {
+            return Err(QueryError::InvalidPath { path: path.clone() }.into());
+        }
+    }
+
     for param in &query.params {
         let (name, value) = match param {
             Parameter::Lookup(s) => (String::from("lookup"), s.clone()),
@@ -56,6 +95,9 @@ where
             Parameter::Developer(s) => (String::from("developer"), s.clone()),
             Parameter::Publisher(s) => (String::from("publisher"), s.clone()),
             Parameter::Moderator(s) => (String::from("moderator"), s.clone()),
+            Parameter::Offset(i) => (String::from("offset"), i.to_string()),
+            Parameter::Max(i) => (String::from("max"), i.to_string()),
+            Parameter::Raw(name, value) => (name.clone(), value.clone()),
         };
 
         url.query_pairs_mut().append_pair(&name, &value);
@@ -66,7 +108,7 @@ where
     let count = url.query_pairs().count();
 
     match query.query_type {
-        QueryType::User { .. } => {
+        QueryType::User { .. } | QueryType::Profile | QueryType::Get { .. } => {
             if count > 0 {
                 return Err(QueryError::WrongParamCountNeq {
                     expected: 0,
@@ -84,7 +126,7 @@ where
                 .into());
             }
         }
-        QueryType::UserPBs { .. } => {}
+        QueryType::UserPBs { .. } | QueryType::List { .. } | QueryType::Raw { .. } => {}
         QueryType::Games => {
             if count == 0 {
                 return Err(QueryError::WrongParamCountGt {
@@ -96,11 +138,176 @@ where
         }
     }
 
-    Ok(serde_json::from_str(
-        &client.client.get(url).send().await?.text().await?,
-    )?)
+    Ok(url)
+}
+
+pub(crate) async fn fetch<T>(
+    client: &SrcClient,
+    kind: QueryKind,
+    url: Url,
+) -> Result<Response<T>, SrcError>
+where
+    T: DeserializeOwned,
+{
+    let response = fetch_body(client, kind, url).await?;
+    let data = decode(&response.data)?;
+
+    Ok(response.map(|_| data))
+}
+
+/// Fetches `url` through the cache and middleware chain, without decoding the
+/// body.
+pub(crate) async fn fetch_body(
+    client: &SrcClient,
+    kind: QueryKind,
+    url: Url,
+) -> Result<Response<Bytes>, SrcError> {
+    #[cfg(feature = "tracing")]
+    let span = tracing::info_span!("srcapi_call", endpoint = ?kind, url = %url);
+
+    let call = fetch_inner(client, kind, url);
+
+    #[cfg(feature = "tracing")]
+    let call = tracing::Instrument::instrument(call, span);
+
+    call.await
+}
+
+async fn fetch_inner(
+    client: &SrcClient,
+    kind: QueryKind,
+    url: Url,
+) -> Result<Response<Bytes>, SrcError> {
+    if client.offline {
+        return fetch_offline(client, kind, url);
+    }
+
+    let response = send(client, kind, Method::GET, url, HeaderMap::new()).await?;
+
+    Ok(Response {
+        data: response.body,
+        freshness: response.freshness,
+        age: response.age,
+    })
+}
+
+/// Serves `url` from the cache whatever its age, as any cached response beats
+/// none while offline. The middleware chain is never involved.
+fn fetch_offline(
+    client: &SrcClient,
+    kind: QueryKind,
+    url: Url,
+) -> Result<Response<Bytes>, SrcError> {
+    let key = cache::key(
+        &url,
+        client.auth.as_ref().map(|auth| auth.identity.as_str()),
+    );
+
+    let cached = client
+        .cache
+        .as_ref()
+        .and_then(|cache| cache.get_offline(&key, kind))
+        .ok_or_else(|| SrcError::Offline {
+            url: url.to_string(),
+        })?;
+
+    #[cfg(feature = "tracing")]
+    tracing::debug!(
+        age_ms = cached.age().as_millis() as u64,
+        "cache hit while offline"
+    );
+
+    let response = cached.into_response(Freshness::Cached);
+
+    Ok(Response {
+        data: response.body,
+        freshness: response.freshness,
+        age: response.age,
+    })
+}
+
+/// Decodes a JSON body. The path to a failing field is only tracked on a
+/// second pass after decoding fails, which keeps the common case fast.
+pub(crate) fn decode<'de, T>(body: &'de [u8]) -> Result<T, SrcError>
+where
+    T: Deserialize<'de>,
+{
+    serde_json::from_slice(body).or_else(|_| {
+        let de = &mut serde_json::Deserializer::from_slice(body);
+
+        serde_path_to_error::deserialize(de)
+            .map_err(|e| SrcError::decode(&String::from_utf8_lossy(body), e))
+    })
+}
+
+/// Sends a request through the client's middleware chain and transport,
+/// turning error statuses into a [`SrcError`]. A `304 Not Modified` counts as
+/// a success.
+///
+/// Identical requests made while this one is in flight wait for its response
+/// instead of going out themselves.
+async fn send(
+    client: &SrcClient,
+    kind: QueryKind,
+    method: Method,
+    url: Url,
+    extra_headers: HeaderMap,
+) -> Result<HttpResponse, SrcError> {
+    let request = prepare(client, kind, method, url, extra_headers)?;
+
+    let response = client
+        .in_flight
+        .send(request, |request| {
+            let client = client.clone();
+
+            Box::pin(async move {
+                Next::new(&client.middleware, &*client.transport)
+                    .run(request)
+                    .await
+            })
+        })
+        .await?;
+
+    match SrcError::from_response(&response) {
+        Some(error) => Err(error),
+        None => Ok(response),
+    }
+}
+
+/// Builds a request with the client's default headers and credentials.
+pub(crate) fn prepare(
+    client: &SrcClient,
+    kind: QueryKind,
+    method: Method,
+    url: Url,
+    extra_headers: HeaderMap,
+) -> Result<HttpRequest, SrcError> {
+    let mut headers = client.default_headers.clone();
+    headers.extend(extra_headers);
+
+    if let Some(auth) = &client.auth {
+        let mut value =
+            HeaderValue::from_str(&auth.api_key).map_err(|_| QueryError::InvalidHeader {
+                name: API_KEY_HEADER.to_string(),
+            })?;
+        value.set_sensitive(true);
+
+        headers.insert(API_KEY_HEADER, value);
+    }
+
+    Ok(HttpRequest {
+        method,
+        url,
+        headers,
+        body: None,
+        priority: client.priority,
+        identity: client.auth.as_ref().map(|auth| auth.identity.clone()),
+        kind,
+    })
 }
 
+/// A request to an endpoint, for sending with [`query`] or rendering with
+/// [`build_url`] when the typed methods of [`SrcClient`] don't cover it.
 #[derive(Clone)]
 pub struct QueryData {
     pub query_type: QueryType,
@@ -114,18 +321,71 @@ impl QueryData {
             params: Vec::new(),
         }
     }
+
+    pub fn param(mut self, param: Parameter) -> Self {
+        self.params.push(param);
+        self
+    }
 }
 
 #[derive(Clone)]
 pub enum QueryType {
-    User { id: String },
+    User {
+        id: String,
+    },
     Users,
-    UserPBs { id: String },
+    UserPBs {
+        id: String,
+    },
+    Profile,
 
     Games,
+
+    /// An item of a [`Resource`](crate::resource::Resource) by its ID.
+    Get {
+        path: &'static str,
+        id: String,
+    },
+    /// The collection of a [`Resource`](crate::resource::Resource).
+    List {
+        path: &'static str,
+    },
+
+    /// Any other endpoint, as a path relative to the base URL (e.g.
+    /// `"runs"` or `"games/abc/categories"`). Its parameters aren't checked.
+    Raw {
+        path: String,
+    },
+}
+
+impl QueryType {
+    pub fn kind(&self) -> QueryKind {
+        match self {
+            QueryType::User { .. } => QueryKind::User,
+            QueryType::Users => QueryKind::Users,
+            QueryType::UserPBs { .. } => QueryKind::UserPBs,
+            QueryType::Profile => QueryKind::Profile,
+            QueryType::Games => QueryKind::Games,
+            QueryType::Get { path, .. } => QueryKind::Get(path),
+            QueryType::List { path } => QueryKind::List(path),
+            QueryType::Raw { .. } => QueryKind::Raw,
+        }
+    }
+}
+
+/// The endpoint a [`QueryType`] targets, without its arguments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QueryKind {
+    User,
+    Users,
+    UserPBs,
+    Profile,
+    Games,
+    Get(&'static str),
+    List(&'static str),
+    Raw,
 }
 
-#[allow(dead_code)]
 #[derive(Clone)]
 pub enum Parameter {
     Lookup(String),
@@ -149,6 +409,76 @@ pub enum Parameter {
     Developer(String),
     Publisher(String),
     Moderator(String),
+    Offset(u32),
+    Max(u32),
+    /// A query parameter the other variants don't cover.
+    Raw(String, String),
+}
+
+/// Filters for listing users. At least one must be set.
+#[derive(Clone, Default)]
+pub struct UsersQuery {
+    pub lookup: Option<String>,
+    pub name: Option<String>,
+    pub twitch: Option<String>,
+    pub hitbox: Option<String>,
+    pub twitter: Option<String>,
+    pub speedrunslive: Option<String>,
+    pub orderby: Option<OrderBy>,
+    pub direction: Option<OrderDirection>,
+}
+
+impl ListQuery for UsersQuery {
+    fn params(&self) -> Vec<Parameter> {
+        let params = [
+            self.lookup.clone().map(Parameter::Lookup),
+            self.name.clone().map(Parameter::Name),
+            self.twitch.clone().map(Parameter::Twitch),
+            self.hitbox.clone().map(Parameter::Hitbox),
+            self.twitter.clone().map(Parameter::Twitter),
+            self.speedrunslive.clone().map(Parameter::Speedrunslive),
+            self.orderby.clone().map(Parameter::OrderBy),
+            self.direction.clone().map(Parameter::Direction),
+        ];
+
+        params.into_iter().flatten().collect()
+    }
+}
+
+/// Filters for listing games. At least one must be set.
+#[derive(Clone, Default)]
+pub struct GamesQuery {
+    pub name: Option<String>,
+    pub abbreviation: Option<String>,
+    pub released: Option<i32>,
+    pub gametype: Option<String>,
+    pub platform: Option<String>,
+    pub region: Option<String>,
+    pub genre: Option<String>,
+    pub engine: Option<String>,
+    pub developer: Option<String>,
+    pub publisher: Option<String>,
+    pub moderator: Option<String>,
+}
+
+impl ListQuery for GamesQuery {
+    fn params(&self) -> Vec<Parameter> {
+        let params = [
+            self.name.clone().map(Parameter::Name),
+            self.abbreviation.clone().map(Parameter::Abbreviation),
+            self.released.map(Parameter::Released),
+            self.gametype.clone().map(Parameter::Gametype),
+            self.platform.clone().map(Parameter::Platform),
+            self.region.clone().map(Parameter::Region),
+            self.genre.clone().map(Parameter::Genre),
+            self.engine.clone().map(Parameter::Engine),
+            self.developer.clone().map(Parameter::Developer),
+            self.publisher.clone().map(Parameter::Publisher),
+            self.moderator.clone().map(Parameter::Moderator),
+        ];
+
+        params.into_iter().flatten().collect()
+    }
 }
 
 #[derive(Clone)]
@@ -165,9 +495,9 @@ pub enum OrderDirection {
     Desc,
 }
 
-#[allow(dead_code)]
-#[derive(Error, Debug)]
-enum QueryError {
+#[derive(Error, Debug, Clone)]
+#[non_exhaustive]
+pub enum QueryError {
     #[error("Invalid query parameter: {name}={value}")]
     InvalidParam { name: String, value: String },
 
@@ -188,4 +518,22 @@ enum QueryError {
 
     #[error("Invalid parameter type: {param_type}")]
     InvalidParamType { param_type: String },
+
+    #[error("Invalid value for header {name}")]
+    InvalidHeader { name: String },
+
+    #[error("This endpoint requires an API key")]
+    MissingApiKey,
+
+    #[error("A rate limit needs a capacity and period above zero")]
+    InvalidRateLimit,
+
+    #[error("Path {path} is outside the API")]
+    InvalidPath { path: String },
+
+    #[error("No credential profile named {name}")]
+    UnknownProfile { name: String },
+
+    #[error("No transport configured; enable the `reqwest` feature or set one on the builder")]
+    MissingTransport,
 }
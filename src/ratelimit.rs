@@ -0,0 +1,549 @@
+use crate::error::SrcError;
+#[cfg(feature = "metrics")]
+use crate::metrics::Metrics;
+use crate::middleware::{Middleware, Next};
+use crate::transport::{HttpRequest, HttpResponse, StreamingResponse};
+use futures::future;
+use futures::future::BoxFuture;
+use futures_timer::Delay;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+
+/// The request budget enforced by a [`RateLimiter`].
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimit {
+    /// Number of requests that may be made per `period`, which is also the
+    /// largest burst the limiter allows.
+    pub capacity: u32,
+    pub period: Duration,
+    /// How long a queued request may wait before it is served ahead of newer
+    /// requests of a higher priority, so low priorities never starve.
+    pub max_queue_time: Duration,
+}
+
+impl RateLimit {
+    /// Whether the limit lets any requests through at all.
+    pub fn is_valid(&self) -> bool {
+        self.capacity > 0 && !self.period.is_zero()
+    }
+}
+
+impl Default for RateLimit {
+    /// speedrun.com's documented budget of 100 requests per minute.
+    fn default() -> Self {
+        Self {
+            capacity: 100,
+            period: Duration::from_secs(60),
+            max_queue_time: Duration::from_secs(10),
+        }
+    }
+}
+
+/// How urgently a request should be sent when it has to wait for the
+/// [`RateLimiter`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Priority {
+    /// E.g. requests a user is actively waiting on.
+    High,
+    #[default]
+    Normal,
+    /// E.g. background crawls.
+    Low,
+}
+
+impl Priority {
+    const ALL: [Priority; 3] = [Priority::High, Priority::Normal, Priority::Low];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// A token bucket that every request made through a
+/// [`SrcClient`](crate::client::SrcClient) draws from.
+///
+/// Clones share the same bucket, so cloned clients stay within one budget.
+/// Requests that have to wait for a token are queued by [`Priority`] and
+/// served highest priority first, except that a request queued for longer
+/// than [`RateLimit::max_queue_time`] goes before everything else.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    limit: RateLimit,
+    bucket: Arc<Mutex<Bucket>>,
+    /// Wakes queued requests whenever the head of a queue may have changed.
+    notify: Arc<Notify>,
+    #[cfg(feature = "metrics")]
+    pub(crate) metrics: Option<Metrics>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled: Instant,
+    queues: [VecDeque<Waiter>; 3],
+    next_id: u64,
+}
+
+#[derive(Debug)]
+struct Waiter {
+    id: u64,
+    queued: Instant,
+}
+
+impl Bucket {
+    fn is_queue_empty(&self) -> bool {
+        self.queues.iter().all(VecDeque::is_empty)
+    }
+
+    fn enqueue(&mut self, priority: Priority) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        self.queues[priority.index()].push_back(Waiter {
+            id,
+            queued: Instant::now(),
+        });
+
+        id
+    }
+
+    fn remove(&mut self, priority: Priority, id: u64) {
+        self.queues[priority.index()].retain(|waiter| waiter.id != id);
+    }
+
+    /// The queued request that gets the next token: the longest waiting one
+    /// if it has waited `max_queue_time`, otherwise the oldest one of the
+    /// highest priority.
+    fn next_in_line(&self, max_queue_time: Duration) -> Option<u64> {
+        let heads = self.queues.iter().filter_map(VecDeque::front);
+
+        let starving = heads
+            .clone()
+            .filter(|waiter| waiter.queued.elapsed() >= max_queue_time)
+            .min_by_key(|waiter| waiter.queued);
+
+        starving.or(heads.clone().next()).map(|waiter| waiter.id)
+    }
+}
+
+/// A request's place in the queue, given up when it is dropped.
+struct Ticket<'a> {
+    limiter: &'a RateLimiter,
+    priority: Priority,
+    id: u64,
+}
+
+impl Drop for Ticket<'_> {
+    fn drop(&mut self) {
+        self.limiter
+            .bucket
+            .lock()
+            .unwrap()
+            .remove(self.priority, self.id);
+        self.limiter.notify.notify_waiters();
+    }
+}
+
+impl RateLimiter {
+    /// # Panics
+    ///
+    /// If the limit has no capacity or period, see [`RateLimit::is_valid`].
+    pub fn new(limit: RateLimit) -> Self {
+        assert!(
+            limit.is_valid(),
+            "a rate limit needs a capacity and period above zero"
+        );
+
+        Self {
+            limit,
+            bucket: Arc::new(Mutex::new(Bucket {
+                tokens: limit.capacity as f64,
+                refilled: Instant::now(),
+                queues: Default::default(),
+                next_id: 0,
+            })),
+            notify: Arc::new(Notify::new()),
+            #[cfg(feature = "metrics")]
+            metrics: None,
+        }
+    }
+
+    pub fn limit(&self) -> RateLimit {
+        self.limit
+    }
+
+    /// A limiter with the same budget and an untouched bucket of its own.
+    fn sibling(&self) -> Self {
+        #[cfg_attr(not(feature = "metrics"), allow(unused_mut))]
+        let mut limiter = Self::new(self.limit);
+
+        #[cfg(feature = "metrics")]
+        {
+            limiter.metrics = self.metrics.clone();
+        }
+
+        limiter
+    }
+
+    /// Number of requests that can be made right now without waiting.
+    pub fn remaining(&self) -> u32 {
+        let mut bucket = self.bucket.lock().unwrap();
+
+        self.refill(&mut bucket);
+
+        bucket.tokens as u32
+    }
+
+    /// Number of requests of the given priority waiting for a token.
+    pub fn queue_depth(&self, priority: Priority) -> usize {
+        self.bucket.lock().unwrap().queues[priority.index()].len()
+    }
+
+    /// Number of requests waiting for a token, for every priority.
+    pub fn queue_depths(&self) -> Vec<(Priority, usize)> {
+        let bucket = self.bucket.lock().unwrap();
+
+        Priority::ALL
+            .iter()
+            .map(|&priority| (priority, bucket.queues[priority.index()].len()))
+            .collect()
+    }
+
+    /// How long until at least one request can be made again.
+    pub fn wait_time(&self) -> Duration {
+        let mut bucket = self.bucket.lock().unwrap();
+
+        self.refill(&mut bucket);
+
+        self.time_until_token(&bucket)
+    }
+
+    /// Waits until a token is available and it is this request's turn, then
+    /// takes the token.
+    pub(crate) async fn acquire(&self, priority: Priority) {
+        #[cfg(feature = "metrics")]
+        let start = Instant::now();
+
+        let mut ticket: Option<Ticket> = None;
+
+        loop {
+            // registered before checking, so a wake-up in between isn't lost
+            let notified = self.notify.notified();
+            let mut notified = std::pin::pin!(notified);
+            notified.as_mut().enable();
+
+            let wait = {
+                let mut bucket = self.bucket.lock().unwrap();
+
+                self.refill(&mut bucket);
+
+                let turn = match &ticket {
+                    Some(ticket) => {
+                        bucket.next_in_line(self.limit.max_queue_time) == Some(ticket.id)
+                    }
+                    None => bucket.is_queue_empty(),
+                };
+
+                if turn && bucket.tokens >= 1.0 {
+                    bucket.tokens -= 1.0;
+
+                    #[cfg(feature = "metrics")]
+                    if let Some(metrics) = &self.metrics {
+                        metrics.observe_rate_limit(start.elapsed(), bucket.tokens as u32);
+                    }
+
+                    // dropping the ticket leaves the queue and wakes the next
+                    drop(bucket);
+                    drop(ticket);
+
+                    return;
+                }
+
+                if ticket.is_none() {
+                    ticket = Some(Ticket {
+                        limiter: self,
+                        priority,
+                        id: bucket.enqueue(priority),
+                    });
+                }
+
+                self.time_until_token(&bucket)
+            };
+
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                wait_ms = wait.as_millis() as u64,
+                ?priority,
+                "waiting for rate limiter"
+            );
+
+            if wait.is_zero() {
+                // a token is ready but another request is first in line
+                notified.await;
+            } else {
+                future::select(notified, Delay::new(wait)).await;
+            }
+        }
+    }
+
+    fn refill(&self, bucket: &mut Bucket) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.refilled);
+
+        bucket.tokens =
+            (bucket.tokens + elapsed.as_secs_f64() * self.rate()).min(self.limit.capacity as f64);
+        bucket.refilled = now;
+    }
+
+    fn time_until_token(&self, bucket: &Bucket) -> Duration {
+        if bucket.tokens >= 1.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64((1.0 - bucket.tokens) / self.rate())
+        }
+    }
+
+    /// Tokens regained per second.
+    fn rate(&self) -> f64 {
+        self.limit.capacity as f64 / self.limit.period.as_secs_f64()
+    }
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new(RateLimit::default())
+    }
+}
+
+/// Waits for a token before every request passed down the chain.
+impl Middleware for RateLimiter {
+    fn handle<'a>(
+        &'a self,
+        request: HttpRequest,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<HttpResponse, SrcError>> {
+        Box::pin(async move {
+            self.acquire(request.priority).await;
+
+            next.run(request).await
+        })
+    }
+
+    fn handle_streaming<'a>(
+        &'a self,
+        request: HttpRequest,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<StreamingResponse, SrcError>> {
+        Box::pin(async move {
+            self.acquire(request.priority).await;
+
+            next.run_streaming(request).await
+        })
+    }
+}
+
+/// The limiters of a client and all of its clones: one for anonymous requests
+/// and one for every identity requests are made as, created on first use.
+pub(crate) struct RateLimiters {
+    anonymous: RateLimiter,
+    scoped: Mutex<HashMap<String, RateLimiter>>,
+}
+
+impl RateLimiters {
+    pub(crate) fn new(anonymous: RateLimiter) -> Self {
+        Self {
+            anonymous,
+            scoped: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) fn get(&self, identity: Option<&str>) -> RateLimiter {
+        match identity {
+            Some(identity) => self
+                .scoped
+                .lock()
+                .unwrap()
+                .entry(identity.to_string())
+                .or_insert_with(|| self.anonymous.sibling())
+                .clone(),
+            None => self.anonymous.clone(),
+        }
+    }
+}
+
+/// Waits for a token from the limiter of the request's identity.
+impl Middleware for RateLimiters {
+    fn handle<'a>(
+        &'a self,
+        request: HttpRequest,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<HttpResponse, SrcError>> {
+        Box::pin(async move {
+            let limiter = self.get(request.identity.as_deref());
+
+            limiter.acquire(request.priority).await;
+
+            next.run(request).await
+        })
+    }
+
+    fn handle_streaming<'a>(
+        &'a self,
+        request: HttpRequest,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<StreamingResponse, SrcError>> {
+        Box::pin(async move {
+            let limiter = self.get(request.identity.as_deref());
+
+            limiter.acquire(request.priority).await;
+
+            next.run_streaming(request).await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::SrcClient;
+    use crate::error::{QueryError, SrcError};
+    use crate::transport::MemoryTransport;
+
+    fn limiter(capacity: u32, period_ms: u64) -> RateLimiter {
+        RateLimiter::new(RateLimit {
+            capacity,
+            period: Duration::from_millis(period_ms),
+            max_queue_time: Duration::from_secs(10),
+        })
+    }
+
+    #[tokio::test]
+    async fn starts_full_and_counts_down() {
+        let limiter = limiter(3, 1000);
+
+        assert_eq!(limiter.remaining(), 3);
+        assert_eq!(limiter.wait_time(), Duration::ZERO);
+
+        for _ in 0..3 {
+            limiter.acquire(Priority::Normal).await;
+        }
+
+        assert_eq!(limiter.remaining(), 0);
+        assert!(limiter.wait_time() > Duration::ZERO);
+        assert!(limiter.wait_time() <= Duration::from_millis(334));
+    }
+
+    #[tokio::test]
+    async fn refills_over_time() {
+        let limiter = limiter(2, 100);
+
+        limiter.acquire(Priority::Normal).await;
+        limiter.acquire(Priority::Normal).await;
+        tokio::time::sleep(Duration::from_millis(60)).await;
+
+        assert_eq!(limiter.remaining(), 1);
+    }
+
+    #[tokio::test]
+    async fn waits_for_the_next_token() {
+        let limiter = limiter(1, 50);
+
+        limiter.acquire(Priority::Normal).await;
+
+        let start = Instant::now();
+        limiter.acquire(Priority::Normal).await;
+
+        assert!(start.elapsed() >= Duration::from_millis(40));
+    }
+
+    /// Waits for a token, then records that `priority` got one.
+    async fn take(limiter: &RateLimiter, priority: Priority, order: &Mutex<Vec<Priority>>) {
+        limiter.acquire(priority).await;
+        order.lock().unwrap().push(priority);
+    }
+
+    #[tokio::test]
+    async fn serves_higher_priorities_first() {
+        let limiter = limiter(1, 50);
+        let order = Mutex::new(Vec::new());
+
+        limiter.acquire(Priority::Normal).await;
+
+        // queued lowest first, so arrival order alone would get it backwards
+        futures::join!(
+            take(&limiter, Priority::Low, &order),
+            take(&limiter, Priority::Normal, &order),
+            take(&limiter, Priority::High, &order),
+        );
+
+        assert_eq!(
+            *order.lock().unwrap(),
+            [Priority::High, Priority::Normal, Priority::Low]
+        );
+    }
+
+    #[tokio::test]
+    async fn promotes_requests_that_waited_too_long() {
+        let limiter = RateLimiter::new(RateLimit {
+            capacity: 1,
+            period: Duration::from_millis(200),
+            max_queue_time: Duration::from_millis(100),
+        });
+        let order = Mutex::new(Vec::new());
+
+        limiter.acquire(Priority::Normal).await;
+
+        // by the time a token comes in, only the low one has waited too long
+        futures::join!(take(&limiter, Priority::Low, &order), async {
+            tokio::time::sleep(Duration::from_millis(150)).await;
+            take(&limiter, Priority::High, &order).await;
+        });
+
+        assert_eq!(*order.lock().unwrap(), [Priority::Low, Priority::High]);
+    }
+
+    #[tokio::test]
+    async fn cancelled_requests_leave_the_queue() {
+        let limiter = limiter(1, 100);
+
+        limiter.acquire(Priority::Normal).await;
+
+        let cancelled =
+            tokio::time::timeout(Duration::from_millis(10), limiter.acquire(Priority::Low)).await;
+
+        assert!(cancelled.is_err());
+        assert_eq!(limiter.queue_depth(Priority::Low), 0);
+
+        // the next request isn't stuck behind the abandoned one
+        tokio::time::timeout(Duration::from_secs(1), limiter.acquire(Priority::Normal))
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn refuses_a_limit_without_capacity() {
+        limiter(0, 1000);
+    }
+
+    #[test]
+    fn the_builder_rejects_invalid_limits() {
+        for (capacity, period_ms) in [(0, 1000), (1, 0)] {
+            let result = SrcClient::builder()
+                .transport(MemoryTransport::new())
+                .rate_limit(RateLimit {
+                    capacity,
+                    period: Duration::from_millis(period_ms),
+                    ..RateLimit::default()
+                })
+                .build();
+
+            assert!(matches!(
+                result,
+                Err(SrcError::Query(QueryError::InvalidRateLimit))
+            ));
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use crate::{
+    games::{Game, GameId},
+    query::{GamesQuery, Parameter, QueryData, QueryType, UsersQuery},
+    runs::{Run, RunId},
+    users::{User, UserId},
+};
+use serde::de::DeserializeOwned;
+use std::fmt;
+
+/// A model the API serves from an endpoint of its own, for fetching with
+/// [`SrcClient::get`](crate::client::SrcClient::get) and
+/// [`SrcClient::list`](crate::client::SrcClient::list).
+///
+/// A new endpoint only needs an implementation of this trait; the default
+/// queries get `{PATH}/{id}` and list `{PATH}` with the query's parameters.
+pub trait Resource: DeserializeOwned + Send + 'static {
+    /// The path of the collection, relative to the base URL.
+    const PATH: &'static str;
+
+    type Id: fmt::Display;
+
+    /// The filters the collection can be listed with.
+    type Query: ListQuery;
+
+    fn get_query(id: &Self::Id) -> QueryData {
+        QueryData::new(QueryType::Get {
+            path: Self::PATH,
+            id: id.to_string(),
+        })
+    }
+
+    fn list_query(query: &Self::Query) -> QueryData {
+        QueryData {
+            query_type: QueryType::List { path: Self::PATH },
+            params: query.params(),
+        }
+    }
+}
+
+/// Filters for listing a [`Resource`].
+pub trait ListQuery {
+    fn params(&self) -> Vec<Parameter>;
+}
+
+/// No filters.
+impl ListQuery for () {
+    fn params(&self) -> Vec<Parameter> {
+        Vec::new()
+    }
+}
+
+impl Resource for User {
+    const PATH: &'static str = "users";
+
+    type Id = UserId;
+    type Query = UsersQuery;
+
+    fn get_query(id: &UserId) -> QueryData {
+        QueryData::new(QueryType::User { id: id.to_string() })
+    }
+
+    fn list_query(query: &UsersQuery) -> QueryData {
+        QueryData {
+            query_type: QueryType::Users,
+            params: query.params(),
+        }
+    }
+}
+
+impl Resource for Game {
+    const PATH: &'static str = "games";
+
+    type Id = GameId;
+    type Query = GamesQuery;
+
+    fn list_query(query: &GamesQuery) -> QueryData {
+        QueryData {
+            query_type: QueryType::Games,
+            params: query.params(),
+        }
+    }
+}
+
+impl Resource for Run {
+    const PATH: &'static str = "runs";
+
+    type Id = RunId;
+    type Query = ();
+}
@@ -0,0 +1,80 @@
+use crate::error::SrcError;
+use bytes::Bytes;
+use serde::Deserialize;
+use std::ops::{Deref, DerefMut};
+use std::time::Duration;
+
+/// Where the data of a [`Response`] came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Freshness {
+    /// Downloaded from the API by this call.
+    Fresh,
+    /// Served from the cache after the API confirmed it was unchanged.
+    Revalidated,
+    /// Served from the cache without asking the API.
+    Cached,
+}
+
+/// The result of a call on a [`SrcClient`](crate::client::SrcClient), along
+/// with where it came from. Derefs to the data itself.
+#[derive(Debug, Clone)]
+pub struct Response<T> {
+    pub data: T,
+    pub freshness: Freshness,
+    /// Time since the data was downloaded or last revalidated.
+    pub age: Duration,
+}
+
+impl<T> Response<T> {
+    pub fn into_inner(self) -> T {
+        self.data
+    }
+
+    pub fn map<U, F>(self, f: F) -> Response<U>
+    where
+        F: FnOnce(T) -> U,
+    {
+        Response {
+            data: f(self.data),
+            freshness: self.freshness,
+            age: self.age,
+        }
+    }
+}
+
+impl Response<Bytes> {
+    /// Decodes the body, which may borrow from it, e.g. into
+    /// [`Page`](crate::pagination::Page)`<`[`games::borrowed::Game`](crate::games::borrowed::Game)`>`.
+    pub fn decode<'a, T>(&'a self) -> Result<T, SrcError>
+    where
+        T: Deserialize<'a>,
+    {
+        crate::query::decode(&self.data)
+    }
+}
+
+impl<T> Deref for Response<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.data
+    }
+}
+
+impl<T> DerefMut for Response<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.data
+    }
+}
+
+impl<T> IntoIterator for Response<T>
+where
+    T: IntoIterator,
+{
+    type Item = T::Item;
+    type IntoIter = T::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.data.into_iter()
+    }
+}
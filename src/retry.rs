@@ -0,0 +1,415 @@
+use crate::error::SrcError;
+#[cfg(feature = "metrics")]
+use crate::metrics::Metrics;
+use crate::middleware::{Middleware, Next};
+use crate::transport::{header::RETRY_AFTER, Method, StatusCode, Url};
+use crate::transport::{HttpRequest, HttpResponse, StreamingResponse, TransportErrorKind};
+use chrono::{DateTime, Utc};
+use futures::future::BoxFuture;
+use futures_timer::Delay;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Controls how failed requests are retried.
+///
+/// Rate limiting (HTTP 420), server errors, timeouts and dropped connections
+/// are retried with exponential backoff. A `Retry-After` header sent by the
+/// API takes precedence over the computed delay.
+///
+/// ```
+/// # use srcapi_plus::retry::RetryPolicy;
+/// # use std::time::Duration;
+/// let policy = RetryPolicy::default()
+///     .max_attempts(5)
+///     .base_delay(Duration::from_secs(1));
+/// ```
+#[derive(Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    jitter: f64,
+    retry_non_idempotent: bool,
+    on_attempt: Option<OnAttempt>,
+    #[cfg(feature = "metrics")]
+    pub(crate) metrics: Option<Metrics>,
+}
+
+type OnAttempt = Arc<dyn Fn(&Attempt) + Send + Sync>;
+
+/// Describes an attempt at a request that has just completed.
+#[derive(Debug)]
+pub struct Attempt<'a> {
+    /// Number of the attempt, starting at 1.
+    pub attempt: u32,
+    pub method: &'a Method,
+    pub url: &'a Url,
+    /// The status the API answered with, if a response came back.
+    pub status: Option<StatusCode>,
+    /// Why the attempt failed, if it did.
+    pub error: Option<&'a SrcError>,
+    /// How long until the request is tried again, if it will be.
+    pub retry_in: Option<Duration>,
+}
+
+impl RetryPolicy {
+    /// A policy that never retries.
+    pub fn none() -> Self {
+        Self::default().max_attempts(1)
+    }
+
+    /// Total number of attempts, including the first one. Defaults to 3.
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Delay before the first retry, doubled for every further attempt.
+    /// Defaults to 500ms.
+    pub fn base_delay(mut self, delay: Duration) -> Self {
+        self.base_delay = delay;
+        self
+    }
+
+    /// Longest delay between two attempts. Defaults to 30s.
+    pub fn max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Fraction (`0.0..=1.0`) of each delay that is randomised. Defaults to
+    /// 0.5.
+    pub fn jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Also retry methods that are not idempotent. Off by default.
+    pub fn retry_non_idempotent(mut self, retry: bool) -> Self {
+        self.retry_non_idempotent = retry;
+        self
+    }
+
+    /// Calls `f` after every attempt, whether it succeeded, failed or is
+    /// about to be retried, e.g. to log it.
+    pub fn on_attempt<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&Attempt) + Send + Sync + 'static,
+    {
+        self.on_attempt = Some(Arc::new(f));
+        self
+    }
+
+    pub(crate) fn should_retry(&self, method: &Method, error: &SrcError) -> bool {
+        if !self.retry_non_idempotent && !is_idempotent(method) {
+            return false;
+        }
+
+        match error {
+            SrcError::RateLimited { .. } => true,
+            SrcError::Api { status, .. } => *status >= 500,
+            SrcError::Transport(e) => e.kind != TransportErrorKind::Other,
+            _ => false,
+        }
+    }
+
+    pub(crate) fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        if let Some(retry_after) = retry_after {
+            return retry_after;
+        }
+
+        let backoff = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_delay);
+
+        let jitter = self.jitter.clamp(0.0, 1.0) * fastrand::f64();
+
+        backoff.mul_f64(1.0 - jitter)
+    }
+
+    pub(crate) fn notify(&self, attempt: &Attempt) {
+        if let Some(on_attempt) = &self.on_attempt {
+            on_attempt(attempt);
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            jitter: 0.5,
+            retry_non_idempotent: false,
+            on_attempt: None,
+            #[cfg(feature = "metrics")]
+            metrics: None,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Sends `request` with `send` again while it fails in a way the policy
+    /// retries.
+    async fn run<'a, T, F>(&self, request: HttpRequest, send: F) -> Result<T, SrcError>
+    where
+        T: Answer,
+        F: Fn(HttpRequest) -> BoxFuture<'a, Result<T, SrcError>>,
+    {
+        let mut attempt = 1;
+
+        loop {
+            let result = send(request.clone()).await;
+
+            let (error, retry_after) = match &result {
+                Ok(response) => (response.error(), response.retry_after()),
+                Err(error) => (Some(error.clone()), None),
+            };
+
+            let retry = error.as_ref().filter(|error| {
+                attempt < self.max_attempts && self.should_retry(&request.method, error)
+            });
+            let delay = retry.map(|_| self.delay(attempt, retry_after));
+
+            self.notify(&Attempt {
+                attempt,
+                method: &request.method,
+                url: &request.url,
+                status: result.as_ref().ok().map(Answer::status),
+                error: error.as_ref(),
+                retry_in: delay,
+            });
+
+            let Some(delay) = delay else {
+                return result;
+            };
+
+            #[cfg(feature = "metrics")]
+            if let (Some(metrics), Some(error)) = (&self.metrics, retry) {
+                metrics.observe_retry(error);
+            }
+
+            #[cfg(feature = "tracing")]
+            if let Some(error) = retry {
+                tracing::warn!(
+                    attempt,
+                    delay_ms = delay.as_millis() as u64,
+                    %error,
+                    "retrying request"
+                );
+            }
+
+            Delay::new(delay).await;
+            attempt += 1;
+        }
+    }
+}
+
+/// A response the policy decides on.
+trait Answer {
+    fn status(&self) -> StatusCode;
+
+    /// The error the response stands for, if any.
+    fn error(&self) -> Option<SrcError>;
+
+    /// How long the API asked to wait before trying again.
+    fn retry_after(&self) -> Option<Duration>;
+}
+
+impl Answer for HttpResponse {
+    fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    fn error(&self) -> Option<SrcError> {
+        SrcError::from_response(self)
+    }
+
+    fn retry_after(&self) -> Option<Duration> {
+        self.header(RETRY_AFTER)
+            .as_deref()
+            .and_then(parse_retry_after)
+    }
+}
+
+/// A streamed response, or the whole error response it turned out to be.
+impl Answer for Result<StreamingResponse, HttpResponse> {
+    fn status(&self) -> StatusCode {
+        match self {
+            Ok(response) => response.status,
+            Err(response) => response.status,
+        }
+    }
+
+    fn error(&self) -> Option<SrcError> {
+        self.as_ref().err().and_then(Answer::error)
+    }
+
+    fn retry_after(&self) -> Option<Duration> {
+        self.as_ref().err().and_then(Answer::retry_after)
+    }
+}
+
+/// Sends the request down the chain again while it fails in a way the policy
+/// retries. Error statuses are passed back up as responses, not errors.
+impl Middleware for RetryPolicy {
+    fn handle<'a>(
+        &'a self,
+        request: HttpRequest,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<HttpResponse, SrcError>> {
+        Box::pin(self.run(request, move |request| next.run(request)))
+    }
+
+    /// Only the headers are waited for: the body of a successful response is
+    /// left to the caller, while error bodies are read whole to tell whether
+    /// to retry.
+    fn handle_streaming<'a>(
+        &'a self,
+        request: HttpRequest,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<StreamingResponse, SrcError>> {
+        Box::pin(async move {
+            let response = self
+                .run(request, move |request| {
+                    Box::pin(async move {
+                        let response = next.run_streaming(request).await?;
+
+                        match response.status.is_success() {
+                            true => Ok(Ok(response)),
+                            false => response.collect().await.map(Err),
+                        }
+                    })
+                })
+                .await?;
+
+            Ok(response.unwrap_or_else(StreamingResponse::from))
+        })
+    }
+}
+
+impl fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("base_delay", &self.base_delay)
+            .field("max_delay", &self.max_delay)
+            .field("jitter", &self.jitter)
+            .field("retry_non_idempotent", &self.retry_non_idempotent)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Parses a `Retry-After` header, given either in seconds or as an HTTP date.
+pub(crate) fn parse_retry_after(value: &str) -> Option<Duration> {
+    if let Ok(secs) = value.trim().parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    let date = DateTime::parse_from_rfc2822(value.trim()).ok()?;
+
+    (date.with_timezone(&Utc) - Utc::now()).to_std().ok()
+}
+
+fn is_idempotent(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::PUT | Method::DELETE | Method::OPTIONS | Method::TRACE
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy::default()
+            .base_delay(Duration::from_millis(100))
+            .max_delay(Duration::from_millis(350))
+            .jitter(0.0)
+    }
+
+    fn server_error() -> SrcError {
+        SrcError::Api {
+            status: 503,
+            message: String::new(),
+            links: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn doubles_the_delay_up_to_the_maximum() {
+        let delays: Vec<_> = (1..=4)
+            .map(|attempt| policy().delay(attempt, None))
+            .collect();
+
+        assert_eq!(
+            delays,
+            [100, 200, 350, 350].map(Duration::from_millis).to_vec()
+        );
+    }
+
+    #[test]
+    fn jitter_only_shortens_the_delay() {
+        let policy = policy().jitter(0.5);
+
+        for _ in 0..100 {
+            let delay = policy.delay(2, None);
+
+            assert!(delay >= Duration::from_millis(100) && delay <= Duration::from_millis(200));
+        }
+    }
+
+    #[test]
+    fn retry_after_takes_precedence() {
+        let retry_after = Some(Duration::from_secs(7));
+
+        assert_eq!(policy().delay(1, retry_after), Duration::from_secs(7));
+    }
+
+    #[test]
+    fn parses_retry_after_in_seconds_and_as_a_date() {
+        assert_eq!(parse_retry_after(" 12 "), Some(Duration::from_secs(12)));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), None);
+        assert_eq!(parse_retry_after("soon"), None);
+
+        let date = (Utc::now() + chrono::Duration::seconds(60)).to_rfc2822();
+        let delay = parse_retry_after(&date).unwrap();
+
+        assert!(delay > Duration::from_secs(50) && delay <= Duration::from_secs(60));
+    }
+
+    #[test]
+    fn only_retries_idempotent_methods_by_default() {
+        let error = server_error();
+
+        assert!(policy().should_retry(&Method::GET, &error));
+        assert!(policy().should_retry(&Method::PUT, &error));
+        assert!(!policy().should_retry(&Method::POST, &error));
+        assert!(policy()
+            .retry_non_idempotent(true)
+            .should_retry(&Method::POST, &error));
+    }
+
+    #[test]
+    fn only_retries_transient_errors() {
+        let not_found = SrcError::Api {
+            status: 404,
+            message: String::new(),
+            links: Vec::new(),
+        };
+
+        assert!(policy().should_retry(&Method::GET, &SrcError::RateLimited { retry_after: None }));
+        assert!(!policy().should_retry(&Method::GET, &not_found));
+        assert!(!policy().should_retry(
+            &Method::GET,
+            &SrcError::UnmatchedRequest {
+                method: "GET".to_string(),
+                url: String::new(),
+            }
+        ));
+    }
+}
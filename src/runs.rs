@@ -0,0 +1,144 @@
+use crate::games::{GameId, PlatformId, RegionId};
+use crate::users::UserId;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A run on a user's personal-bests list, with its place on the leaderboard.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PersonalBest {
+    pub place: u32,
+    pub run: Run,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Run {
+    pub id: RunId,
+    pub weblink: Weblink,
+    pub game: GameId,
+    pub level: Option<LevelId>,
+    pub category: CategoryId,
+    pub videos: Option<Videos>,
+    pub comment: Option<String>,
+    pub status: Status,
+    pub players: Vec<Player>,
+    pub date: Option<String>,
+    pub submitted: Option<DateTime<Utc>>,
+    pub times: Times,
+    pub system: System,
+    pub splits: Option<Link>,
+    /// Variable IDs mapped to the IDs of their chosen values.
+    pub values: HashMap<String, String>,
+    pub links: Option<Links>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RunId(String);
+
+impl From<String> for RunId {
+    fn from(id: String) -> Self {
+        Self(id)
+    }
+}
+
+impl From<&str> for RunId {
+    fn from(id: &str) -> Self {
+        Self(id.to_string())
+    }
+}
+
+impl std::fmt::Display for RunId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Weblink(String);
+
+impl std::fmt::Display for Weblink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LevelId(String);
+
+impl std::fmt::Display for LevelId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CategoryId(String);
+
+impl std::fmt::Display for CategoryId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Videos {
+    pub text: Option<String>,
+    pub links: Option<Vec<Video>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Video {
+    pub uri: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum Status {
+    New,
+    Verified {
+        examiner: Option<UserId>,
+        #[serde(rename = "verify-date")]
+        verify_date: Option<DateTime<Utc>>,
+    },
+    Rejected {
+        examiner: Option<UserId>,
+        reason: Option<String>,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "rel", rename_all = "lowercase")]
+pub enum Player {
+    User { id: UserId, uri: String },
+    Guest { name: String, uri: String },
+}
+
+/// The run's times as ISO 8601 durations, along with the same times in
+/// seconds (`*_t`, zero when not set).
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Times {
+    pub primary: String,
+    pub primary_t: f64,
+    pub realtime: Option<String>,
+    pub realtime_t: f64,
+    pub realtime_noloads: Option<String>,
+    pub realtime_noloads_t: f64,
+    pub ingame: Option<String>,
+    pub ingame_t: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct System {
+    pub platform: Option<PlatformId>,
+    pub emulated: bool,
+    pub region: Option<RegionId>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Links(Vec<Link>);
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Link {
+    pub rel: Option<String>,
+    pub uri: String,
+}
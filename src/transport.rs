@@ -0,0 +1,322 @@
+use crate::error::SrcError;
+use crate::query::QueryKind;
+use crate::ratelimit::Priority;
+use crate::response::Freshness;
+use bytes::Bytes;
+use futures::future::BoxFuture;
+use futures::stream::{self, BoxStream, StreamExt, TryStreamExt};
+use header::{HeaderMap, HeaderName, HeaderValue};
+#[cfg(feature = "reqwest")]
+use reqwest::Client;
+use std::collections::{HashMap, VecDeque};
+use std::error::Error as StdError;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use thiserror::Error;
+
+pub use http::{header, Method, StatusCode};
+pub use url::Url;
+
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    pub method: Method,
+    pub url: Url,
+    pub headers: HeaderMap,
+    pub body: Option<Bytes>,
+    /// Where the request queues when it has to wait for the rate limiter.
+    pub priority: Priority,
+    /// The credential profile the request is made as, if any. Never the API
+    /// key itself.
+    pub identity: Option<String>,
+    /// The endpoint the request is for, e.g. to pick how long its response
+    /// is cached.
+    pub kind: QueryKind,
+}
+
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+    /// Where the response came from. Transports leave it at
+    /// [`Freshness::Fresh`]; the cache layer sets it when it answers.
+    pub freshness: Freshness,
+    /// Time since the response was downloaded or last revalidated.
+    pub age: Duration,
+}
+
+impl HttpResponse {
+    pub fn new(status: StatusCode, body: impl Into<Bytes>) -> Self {
+        Self {
+            status,
+            headers: HeaderMap::new(),
+            body: body.into(),
+            freshness: Freshness::Fresh,
+            age: Duration::ZERO,
+        }
+    }
+
+    pub fn with_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.append(name, value);
+        self
+    }
+
+    pub(crate) fn header(&self, name: HeaderName) -> Option<String> {
+        self.headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(String::from)
+    }
+}
+
+/// The body of a [`StreamingResponse`], as it arrives.
+pub type BodyStream = BoxStream<'static, Result<Bytes, SrcError>>;
+
+/// A response whose body has not been read yet.
+pub struct StreamingResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: BodyStream,
+}
+
+impl StreamingResponse {
+    /// Reads the whole body.
+    pub async fn collect(self) -> Result<HttpResponse, SrcError> {
+        let body: Vec<u8> = self
+            .body
+            .try_fold(Vec::new(), |mut body, chunk| async move {
+                body.extend_from_slice(&chunk);
+                Ok(body)
+            })
+            .await?;
+
+        Ok(HttpResponse {
+            headers: self.headers,
+            ..HttpResponse::new(self.status, body)
+        })
+    }
+}
+
+impl From<HttpResponse> for StreamingResponse {
+    fn from(response: HttpResponse) -> Self {
+        Self {
+            status: response.status,
+            headers: response.headers,
+            body: stream::once(async move { Ok(response.body) }).boxed(),
+        }
+    }
+}
+
+/// Sends HTTP requests on behalf of a [`SrcClient`](crate::client::SrcClient).
+///
+/// Implementations only move bytes: any response, whatever its status, is
+/// returned as `Ok`. Failing to get a response at all is reported as
+/// [`SrcError::Transport`].
+pub trait Transport: Send + Sync {
+    fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, SrcError>>;
+
+    /// Like [`Transport::send`], but returns as soon as the headers are in and
+    /// hands out the body as it arrives. By default the whole body is read
+    /// first.
+    fn send_streaming(
+        &self,
+        request: HttpRequest,
+    ) -> BoxFuture<'_, Result<StreamingResponse, SrcError>> {
+        Box::pin(async move { self.send(request).await.map(StreamingResponse::from) })
+    }
+}
+
+impl<T> Transport for Arc<T>
+where
+    T: Transport + ?Sized,
+{
+    fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, SrcError>> {
+        (**self).send(request)
+    }
+
+    fn send_streaming(
+        &self,
+        request: HttpRequest,
+    ) -> BoxFuture<'_, Result<StreamingResponse, SrcError>> {
+        (**self).send_streaming(request)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransportErrorKind {
+    Timeout,
+    Connect,
+    ConnectionReset,
+    Other,
+}
+
+#[derive(Error, Debug, Clone)]
+#[error("{source}")]
+pub struct TransportError {
+    pub kind: TransportErrorKind,
+    source: Arc<dyn StdError + Send + Sync>,
+}
+
+impl TransportError {
+    pub fn new(
+        kind: TransportErrorKind,
+        source: impl Into<Box<dyn StdError + Send + Sync>>,
+    ) -> Self {
+        Self {
+            kind,
+            source: Arc::from(source.into()),
+        }
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl From<reqwest::Error> for TransportError {
+    fn from(error: reqwest::Error) -> Self {
+        let kind = if error.is_timeout() {
+            TransportErrorKind::Timeout
+        } else if error.is_connect() {
+            TransportErrorKind::Connect
+        } else if is_connection_reset(&error) {
+            TransportErrorKind::ConnectionReset
+        } else {
+            TransportErrorKind::Other
+        };
+
+        Self::new(kind, error)
+    }
+}
+
+#[cfg(feature = "reqwest")]
+fn is_connection_reset(error: &reqwest::Error) -> bool {
+    let mut source = error.source();
+
+    while let Some(e) = source {
+        if let Some(io) = e.downcast_ref::<std::io::Error>() {
+            return io.kind() == std::io::ErrorKind::ConnectionReset;
+        }
+
+        source = e.source();
+    }
+
+    false
+}
+
+/// The default [`Transport`], backed by a `reqwest::Client`.
+#[cfg(feature = "reqwest")]
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestTransport {
+    client: Client,
+}
+
+#[cfg(feature = "reqwest")]
+impl ReqwestTransport {
+    pub fn new(client: Client) -> Self {
+        Self { client }
+    }
+
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl Transport for ReqwestTransport {
+    fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, SrcError>> {
+        Box::pin(async move {
+            let mut builder = self
+                .client
+                .request(request.method, request.url)
+                .headers(request.headers);
+
+            if let Some(body) = request.body {
+                builder = builder.body(body);
+            }
+
+            let response = builder.send().await?;
+
+            let status = response.status();
+            let headers = response.headers().clone();
+
+            Ok(HttpResponse {
+                headers,
+                ..HttpResponse::new(status, response.bytes().await?)
+            })
+        })
+    }
+
+    fn send_streaming(
+        &self,
+        request: HttpRequest,
+    ) -> BoxFuture<'_, Result<StreamingResponse, SrcError>> {
+        Box::pin(async move {
+            let mut builder = self
+                .client
+                .request(request.method, request.url)
+                .headers(request.headers);
+
+            if let Some(body) = request.body {
+                builder = builder.body(body);
+            }
+
+            let response = builder.send().await?;
+
+            Ok(StreamingResponse {
+                status: response.status(),
+                headers: response.headers().clone(),
+                body: response.bytes_stream().map_err(SrcError::from).boxed(),
+            })
+        })
+    }
+}
+
+/// A [`Transport`] that serves canned responses, for tests.
+///
+/// Responses queued for the same method and URL are served in order, and the
+/// last one keeps being served once the others are used up. Requests without
+/// a response fail with [`SrcError::UnmatchedRequest`].
+#[derive(Debug, Default)]
+pub struct MemoryTransport {
+    responses: Mutex<HashMap<(Method, String), VecDeque<HttpResponse>>>,
+    requests: Mutex<Vec<HttpRequest>>,
+}
+
+impl MemoryTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn respond(&self, method: Method, url: &str, response: HttpResponse) {
+        self.responses
+            .lock()
+            .unwrap()
+            .entry((method, url.to_string()))
+            .or_default()
+            .push_back(response);
+    }
+
+    /// Every request sent so far, in order.
+    pub fn requests(&self) -> Vec<HttpRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+impl Transport for MemoryTransport {
+    fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, SrcError>> {
+        let key = (request.method.clone(), request.url.to_string());
+
+        self.requests.lock().unwrap().push(request);
+
+        let response = match self.responses.lock().unwrap().get_mut(&key) {
+            Some(queue) if queue.len() > 1 => queue.pop_front(),
+            Some(queue) => queue.front().cloned(),
+            None => None,
+        };
+
+        let result = response.ok_or_else(|| SrcError::UnmatchedRequest {
+            method: key.0.to_string(),
+            url: key.1,
+        });
+
+        Box::pin(async move { result })
+    }
+}
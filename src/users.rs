@@ -1,6 +1,8 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+pub mod borrowed;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct User {
     pub id: UserId,
@@ -26,6 +28,18 @@ pub struct User {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UserId(String);
 
+impl From<String> for UserId {
+    fn from(id: String) -> Self {
+        Self(id)
+    }
+}
+
+impl From<&str> for UserId {
+    fn from(id: &str) -> Self {
+        Self(id.to_string())
+    }
+}
+
 impl std::fmt::Display for UserId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         write!(f, "{}", self.0)
@@ -0,0 +1,280 @@
+//! Borrowed counterparts of the user models, for decoding many users without
+//! allocating a `String` for every field. Strings borrow from the response
+//! body unless they contain escapes.
+//!
+//! They save less than they might seem to: most of the time spent decoding a
+//! page used to go to tracking the path to a failing field, which is now
+//! only done once decoding has failed, for the owned models too. On the
+//! `decode` benchmark, borrowed users decode only about 10% faster than owned
+//! ones, so they are mostly worth it for large pages.
+
+use super::Role;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct User<'a> {
+    #[serde(borrow)]
+    pub id: UserId<'a>,
+    #[serde(borrow)]
+    pub names: Names<'a>,
+    #[serde(rename = "supporterAnimation")]
+    pub supporter_animation: bool,
+    #[serde(borrow)]
+    pub pronouns: Pronouns<'a>,
+    #[serde(borrow)]
+    pub weblink: Weblink<'a>,
+    #[serde(rename = "name-style", borrow)]
+    pub name_style: NameStyle<'a>,
+    pub role: Role,
+    pub signup: Option<DateTime<Utc>>,
+    #[serde(borrow)]
+    pub location: Location<'a>,
+    #[serde(borrow)]
+    pub twitch: Option<Social<'a>>,
+    #[serde(borrow)]
+    pub hitbox: Option<Social<'a>>,
+    #[serde(borrow)]
+    pub youtube: Option<Social<'a>>,
+    #[serde(borrow)]
+    pub twitter: Option<Social<'a>>,
+    #[serde(borrow)]
+    pub speedrunslive: Option<Social<'a>>,
+    #[serde(borrow)]
+    pub assets: Assets<'a>,
+    #[serde(borrow)]
+    pub links: Links<'a>,
+}
+
+impl User<'_> {
+    pub fn into_owned(self) -> super::User {
+        super::User {
+            id: self.id.into_owned(),
+            names: self.names.into_owned(),
+            supporter_animation: self.supporter_animation,
+            pronouns: self.pronouns.into_owned(),
+            weblink: self.weblink.into_owned(),
+            name_style: self.name_style.into_owned(),
+            role: self.role,
+            signup: self.signup,
+            location: self.location.into_owned(),
+            twitch: self.twitch.map(Social::into_owned),
+            hitbox: self.hitbox.map(Social::into_owned),
+            youtube: self.youtube.map(Social::into_owned),
+            twitter: self.twitter.map(Social::into_owned),
+            speedrunslive: self.speedrunslive.map(Social::into_owned),
+            assets: self.assets.into_owned(),
+            links: self.links.into_owned(),
+        }
+    }
+}
+
+borrowed_id!(UserId, Pronouns, Weblink);
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Names<'a> {
+    #[serde(borrow)]
+    pub international: Cow<'a, str>,
+    #[serde(borrow, default, deserialize_with = "crate::cow::option")]
+    pub japanese: Option<Cow<'a, str>>,
+}
+
+impl Names<'_> {
+    pub fn into_owned(self) -> super::Names {
+        super::Names {
+            international: self.international.into_owned(),
+            japanese: self.japanese.map(Cow::into_owned),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NameStyle<'a> {
+    #[serde(borrow)]
+    pub style: Cow<'a, str>,
+    #[serde(rename = "color-from", borrow)]
+    pub color_from: Color<'a>,
+    #[serde(rename = "color-to", borrow)]
+    pub color_to: Color<'a>,
+}
+
+impl NameStyle<'_> {
+    pub fn into_owned(self) -> super::NameStyle {
+        super::NameStyle {
+            style: self.style.into_owned(),
+            color_from: self.color_from.into_owned(),
+            color_to: self.color_to.into_owned(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Color<'a> {
+    #[serde(borrow)]
+    pub light: Cow<'a, str>,
+    #[serde(borrow)]
+    pub dark: Cow<'a, str>,
+}
+
+impl Color<'_> {
+    pub fn into_owned(self) -> super::Color {
+        super::Color {
+            light: self.light.into_owned(),
+            dark: self.dark.into_owned(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Location<'a> {
+    #[serde(borrow)]
+    pub country: Country<'a>,
+    #[serde(borrow)]
+    pub region: Option<Region<'a>>,
+}
+
+impl Location<'_> {
+    pub fn into_owned(self) -> super::Location {
+        super::Location {
+            country: self.country.into_owned(),
+            region: self.region.map(Region::into_owned),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Country<'a> {
+    #[serde(borrow)]
+    pub code: Cow<'a, str>,
+    #[serde(borrow)]
+    pub names: Names<'a>,
+}
+
+impl Country<'_> {
+    pub fn into_owned(self) -> super::Country {
+        super::Country {
+            code: self.code.into_owned(),
+            names: self.names.into_owned(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Region<'a> {
+    #[serde(borrow)]
+    pub code: Cow<'a, str>,
+    #[serde(borrow)]
+    pub names: Names<'a>,
+}
+
+impl Region<'_> {
+    pub fn into_owned(self) -> super::Region {
+        super::Region {
+            code: self.code.into_owned(),
+            names: self.names.into_owned(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Social<'a> {
+    #[serde(borrow)]
+    pub uri: Cow<'a, str>,
+}
+
+impl Social<'_> {
+    pub fn into_owned(self) -> super::Social {
+        super::Social {
+            uri: self.uri.into_owned(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Assets<'a> {
+    #[serde(borrow)]
+    pub icon: Icon<'a>,
+    #[serde(rename = "supporterIcon", borrow)]
+    pub supporter_icon: Option<SupporterIcon<'a>>,
+    #[serde(borrow)]
+    pub image: Image<'a>,
+}
+
+impl Assets<'_> {
+    pub fn into_owned(self) -> super::Assets {
+        super::Assets {
+            icon: self.icon.into_owned(),
+            supporter_icon: self.supporter_icon.map(SupporterIcon::into_owned),
+            image: self.image.into_owned(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Icon<'a> {
+    #[serde(borrow, default, deserialize_with = "crate::cow::option")]
+    pub uri: Option<Cow<'a, str>>,
+}
+
+impl Icon<'_> {
+    pub fn into_owned(self) -> super::Icon {
+        super::Icon {
+            uri: self.uri.map(Cow::into_owned),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SupporterIcon<'a> {
+    #[serde(borrow)]
+    pub uri: Cow<'a, str>,
+}
+
+impl SupporterIcon<'_> {
+    pub fn into_owned(self) -> super::SupporterIcon {
+        super::SupporterIcon {
+            uri: self.uri.into_owned(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Image<'a> {
+    #[serde(borrow, default, deserialize_with = "crate::cow::option")]
+    pub uri: Option<Cow<'a, str>>,
+}
+
+impl Image<'_> {
+    pub fn into_owned(self) -> super::Image {
+        super::Image {
+            uri: self.uri.map(Cow::into_owned),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Links<'a>(#[serde(borrow)] Vec<Link<'a>>);
+
+impl Links<'_> {
+    pub fn into_owned(self) -> super::Links {
+        super::Links(self.0.into_iter().map(Link::into_owned).collect())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Link<'a> {
+    #[serde(borrow)]
+    pub rel: Cow<'a, str>,
+    #[serde(borrow)]
+    pub uri: Cow<'a, str>,
+}
+
+impl Link<'_> {
+    pub fn into_owned(self) -> super::Link {
+        super::Link {
+            rel: self.rel.into_owned(),
+            uri: self.uri.into_owned(),
+        }
+    }
+}
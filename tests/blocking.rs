@@ -0,0 +1,65 @@
+#![cfg(feature = "blocking")]
+
+mod common;
+
+use common::{response, user, users_page, BASE};
+use srcapi_plus::blocking::SrcClient;
+use srcapi_plus::pagination::Paging;
+use srcapi_plus::query::UsersQuery;
+use srcapi_plus::transport::{MemoryTransport, Method};
+use srcapi_plus::users::User;
+use std::sync::Arc;
+
+fn client(transport: Arc<MemoryTransport>) -> SrcClient {
+    SrcClient::builder()
+        .transport(transport)
+        .build_blocking()
+        .unwrap()
+}
+
+#[test]
+fn gets_a_user() {
+    let transport = Arc::new(MemoryTransport::new());
+
+    transport.respond(
+        Method::GET,
+        &format!("{}/users/a", BASE),
+        response(200, &format!(r#"{{"data":{}}}"#, user("a"))),
+    );
+
+    let user = client(transport.clone()).get_user("a".to_string()).unwrap();
+
+    assert_eq!(user.id.to_string(), "a");
+    assert_eq!(transport.requests().len(), 1);
+}
+
+#[test]
+fn iterates_over_every_page() {
+    let transport = Arc::new(MemoryTransport::new());
+    let second = format!("{}/users?name=a&offset=2", BASE);
+
+    transport.respond(
+        Method::GET,
+        &format!("{}/users?name=a", BASE),
+        response(200, &users_page(&["a", "b"], 0, Some(&second))),
+    );
+    transport.respond(
+        Method::GET,
+        &second,
+        response(200, &users_page(&["c"], 2, None)),
+    );
+
+    let client = client(transport);
+    let query = UsersQuery {
+        name: Some("a".to_string()),
+        ..UsersQuery::default()
+    };
+
+    let users: Vec<User> = client
+        .list_stream::<User>(query, Paging::default())
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    let ids: Vec<String> = users.iter().map(|user| user.id.to_string()).collect();
+    assert_eq!(ids, ["a", "b", "c"]);
+}
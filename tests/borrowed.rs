@@ -0,0 +1,83 @@
+mod common;
+
+use common::{game, page, response, user, BASE};
+use serde::Serialize;
+use srcapi_plus::client::SrcClient;
+use srcapi_plus::games::{self, Game};
+use srcapi_plus::pagination::Page;
+use srcapi_plus::query::GamesQuery;
+use srcapi_plus::transport::{MemoryTransport, Method};
+use srcapi_plus::users::{self, User};
+use std::borrow::Cow;
+
+fn json(value: &impl Serialize) -> serde_json::Value {
+    serde_json::to_value(value).unwrap()
+}
+
+#[test]
+fn borrowed_games_convert_to_the_owned_decode() {
+    let body = page(&[game("a"), game("b")]);
+
+    let owned: Page<Game> = serde_json::from_str(&body).unwrap();
+    let borrowed: Page<games::borrowed::Game> = serde_json::from_str(&body).unwrap();
+
+    let converted: Vec<Game> = borrowed
+        .data
+        .into_iter()
+        .map(games::borrowed::Game::into_owned)
+        .collect();
+
+    assert_eq!(json(&converted), json(&owned.data));
+}
+
+#[test]
+fn borrowed_users_convert_to_the_owned_decode() {
+    let body = page(&[user("a"), user("b")]);
+
+    let owned: Page<User> = serde_json::from_str(&body).unwrap();
+    let borrowed: Page<users::borrowed::User> = serde_json::from_str(&body).unwrap();
+
+    let converted: Vec<User> = borrowed
+        .data
+        .into_iter()
+        .map(users::borrowed::User::into_owned)
+        .collect();
+
+    assert_eq!(json(&converted), json(&owned.data));
+}
+
+#[test]
+fn strings_borrow_unless_escaped() {
+    let body = game("a");
+    let game: games::borrowed::Game = serde_json::from_str(&body).unwrap();
+
+    assert!(matches!(game.names.twitch, Some(Cow::Borrowed(_))));
+    assert!(matches!(game.ruleset.run_times[0], Cow::Borrowed(_)));
+    assert!(matches!(game.ruleset.default_time, Cow::Borrowed(_)));
+    assert!(matches!(game.names.international, Cow::Owned(_)));
+    assert_eq!(game.names.international, "Game \"a\"");
+}
+
+#[tokio::test]
+async fn list_body_decodes_into_borrowed_models() {
+    let transport = MemoryTransport::new();
+    transport.respond(
+        Method::GET,
+        &format!("{}/games?abbreviation=a", BASE),
+        response(200, &page(&[game("a")])),
+    );
+
+    let client = SrcClient::builder().transport(transport).build().unwrap();
+
+    let body = client
+        .list_body::<Game>(GamesQuery {
+            abbreviation: Some("a".to_string()),
+            ..GamesQuery::default()
+        })
+        .await
+        .unwrap();
+    let games: Page<games::borrowed::Game> = body.decode().unwrap();
+
+    assert_eq!(games.data[0].abbreviation.to_string(), "a");
+    assert!(matches!(games.data[0].names.twitch, Some(Cow::Borrowed(_))));
+}
@@ -0,0 +1,132 @@
+mod common;
+
+use common::{response, user, BASE};
+use srcapi_plus::cache::CacheConfig;
+use srcapi_plus::client::SrcClient;
+use srcapi_plus::response::Freshness;
+use srcapi_plus::transport::header::{HeaderValue, ETAG, IF_NONE_MATCH};
+use srcapi_plus::transport::{MemoryTransport, Method};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn data(item: &str) -> String {
+    format!(r#"{{"data":{}}}"#, item)
+}
+
+fn cached(ttl: Duration) -> (SrcClient, Arc<MemoryTransport>) {
+    let transport = Arc::new(MemoryTransport::new());
+
+    let client = SrcClient::builder()
+        .transport(transport.clone())
+        .cache(CacheConfig::new(ttl))
+        .build()
+        .unwrap();
+
+    (client, transport)
+}
+
+#[tokio::test]
+async fn serves_fresh_responses_from_the_cache() {
+    let (client, transport) = cached(Duration::from_secs(60 * 60));
+
+    transport.respond(
+        Method::GET,
+        &format!("{}/users/a", BASE),
+        response(200, &data(&user("a"))),
+    );
+
+    let first = client.get_user("a".to_string()).await.unwrap();
+    let second = client.get_user("a".to_string()).await.unwrap();
+
+    assert_eq!(first.freshness, Freshness::Fresh);
+    assert_eq!(second.freshness, Freshness::Cached);
+    assert_eq!(second.id.to_string(), "a");
+    assert_eq!(transport.requests().len(), 1);
+
+    let stats = client.cache().unwrap().stats();
+    assert_eq!((stats.hits, stats.misses), (1, 1));
+}
+
+#[tokio::test]
+async fn revalidates_stale_responses() {
+    let (client, transport) = cached(Duration::ZERO);
+    let url = format!("{}/users/a", BASE);
+
+    transport.respond(
+        Method::GET,
+        &url,
+        response(200, &data(&user("a"))).with_header(ETAG, HeaderValue::from_static("\"v1\"")),
+    );
+    transport.respond(Method::GET, &url, response(304, ""));
+
+    let first = client.get_user("a".to_string()).await.unwrap();
+    let second = client.get_user("a".to_string()).await.unwrap();
+
+    assert_eq!(first.freshness, Freshness::Fresh);
+    assert_eq!(second.freshness, Freshness::Revalidated);
+    assert_eq!(second.id.to_string(), "a");
+
+    let requests = transport.requests();
+    assert_eq!(requests.len(), 2);
+    assert!(requests[0].headers.get(IF_NONE_MATCH).is_none());
+    assert_eq!(requests[1].headers.get(IF_NONE_MATCH).unwrap(), "\"v1\"");
+    assert_eq!(client.cache().unwrap().stats().revalidations, 1);
+}
+
+#[tokio::test]
+async fn keeps_the_validators_of_a_304() {
+    let (client, transport) = cached(Duration::ZERO);
+    let url = format!("{}/users/a", BASE);
+
+    transport.respond(
+        Method::GET,
+        &url,
+        response(200, &data(&user("a"))).with_header(ETAG, HeaderValue::from_static("\"v1\"")),
+    );
+    transport.respond(
+        Method::GET,
+        &url,
+        response(304, "").with_header(ETAG, HeaderValue::from_static("\"v2\"")),
+    );
+
+    for _ in 0..3 {
+        client.get_user("a".to_string()).await.unwrap();
+    }
+
+    let sent: Vec<_> = transport
+        .requests()
+        .iter()
+        .map(|request| request.headers.get(IF_NONE_MATCH).cloned())
+        .collect();
+
+    assert_eq!(
+        sent,
+        [
+            None,
+            Some(HeaderValue::from_static("\"v1\"")),
+            Some(HeaderValue::from_static("\"v2\"")),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn invalidates_the_responses_of_every_identity() {
+    let transport = Arc::new(MemoryTransport::new());
+    let url = format!("{}/users/a", BASE);
+
+    transport.respond(Method::GET, &url, response(200, &data(&user("a"))));
+
+    let client = SrcClient::builder()
+        .transport(transport.clone())
+        .api_key("secret")
+        .cache(CacheConfig::new(Duration::from_secs(60 * 60)))
+        .build()
+        .unwrap();
+
+    client.get_user("a".to_string()).await.unwrap();
+    client.cache().unwrap().invalidate(&url);
+    let refetched = client.get_user("a".to_string()).await.unwrap();
+
+    assert_eq!(refetched.freshness, Freshness::Fresh);
+    assert_eq!(transport.requests().len(), 2);
+}
@@ -0,0 +1,133 @@
+mod common;
+
+use common::{fixture, BASE};
+use futures::TryStreamExt;
+use srcapi_plus::cassette::Cassette;
+use srcapi_plus::client::SrcClient;
+use srcapi_plus::error::SrcError;
+use srcapi_plus::pagination::Paging;
+use srcapi_plus::query::UsersQuery;
+use srcapi_plus::retry::RetryPolicy;
+use srcapi_plus::transport::MemoryTransport;
+use srcapi_plus::users::User;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A client that replays `name`, with a transport that fails every request,
+/// so nothing gets past the cassette unnoticed.
+fn replaying(name: &str) -> (SrcClient, Arc<MemoryTransport>) {
+    let transport = Arc::new(MemoryTransport::new());
+
+    let client = SrcClient::builder()
+        .transport(transport.clone())
+        .cassette(Cassette::replay(fixture(name)).unwrap())
+        .retry_policy(RetryPolicy::none())
+        .api_key("abc")
+        .build()
+        .unwrap();
+
+    (client, transport)
+}
+
+#[tokio::test]
+async fn decodes_the_error_envelope() {
+    let (client, transport) = replaying("errors.json");
+
+    let err = client.get_user("missing".to_string()).await.unwrap_err();
+
+    match err {
+        SrcError::Api {
+            status,
+            message,
+            links,
+        } => {
+            assert_eq!(status, 404);
+            assert_eq!(message, "The user could not be found.");
+            assert_eq!(links.len(), 1);
+            assert_eq!(links[0].rel, "support");
+        }
+        other => panic!("expected an API error, got {:?}", other),
+    }
+    assert!(transport.requests().is_empty());
+}
+
+#[tokio::test]
+async fn falls_back_to_the_status_without_an_envelope() {
+    let (client, _) = replaying("errors.json");
+
+    let err = client.get_user("plain".to_string()).await.unwrap_err();
+
+    assert!(matches!(
+        err,
+        SrcError::Api { status: 500, ref message, .. } if message == "Internal Server Error"
+    ));
+}
+
+#[tokio::test]
+async fn maps_401_and_403_to_unauthorized() {
+    let (client, _) = replaying("errors.json");
+
+    let err = client.get_profile().await.unwrap_err();
+    assert!(matches!(
+        err,
+        SrcError::Unauthorized { status: 401, ref message } if message == "You must supply an API key."
+    ));
+
+    let err = client.get_user("private".to_string()).await.unwrap_err();
+    assert!(matches!(
+        err,
+        SrcError::Unauthorized { status: 403, ref message } if message == "This user is private."
+    ));
+}
+
+#[tokio::test]
+async fn reads_retry_after_when_rate_limited() {
+    let (client, _) = replaying("errors.json");
+
+    let err = client.get_user("busy".to_string()).await.unwrap_err();
+
+    assert!(matches!(
+        err,
+        SrcError::RateLimited { retry_after: Some(retry_after) } if retry_after == Duration::from_secs(30)
+    ));
+}
+
+#[tokio::test]
+async fn follows_next_links() {
+    let (client, transport) = replaying("users_pages.json");
+
+    let query = UsersQuery {
+        name: Some("a".to_string()),
+        ..UsersQuery::default()
+    };
+    let paging = Paging {
+        max: Some(2),
+        ..Paging::default()
+    };
+
+    let users: Vec<User> = client
+        .list_stream::<User>(query, paging)
+        .try_collect()
+        .await
+        .unwrap();
+
+    let ids: Vec<String> = users.iter().map(|user| user.id.to_string()).collect();
+    assert_eq!(ids, ["a1", "a2", "a3"]);
+    assert!(transport.requests().is_empty());
+}
+
+#[tokio::test]
+async fn fails_on_an_unmatched_request() {
+    let (client, transport) = replaying("empty.json");
+
+    let err = client.get_user("nobody".to_string()).await.unwrap_err();
+
+    match err {
+        SrcError::UnmatchedRequest { method, url } => {
+            assert_eq!(method, "GET");
+            assert_eq!(url, format!("{}/users/nobody", BASE));
+        }
+        other => panic!("expected an unmatched request, got {:?}", other),
+    }
+    assert!(transport.requests().is_empty());
+}
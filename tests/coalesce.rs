@@ -0,0 +1,110 @@
+mod common;
+
+use common::{response, Delay, BASE};
+use srcapi_plus::client::SrcClient;
+use srcapi_plus::ratelimit::{Priority, RateLimit};
+use srcapi_plus::transport::{MemoryTransport, Method};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn client(transport: Arc<MemoryTransport>) -> SrcClient {
+    transport.respond(
+        Method::GET,
+        &format!("{}/runs", BASE),
+        response(200, r#"{"data":[]}"#),
+    );
+
+    SrcClient::builder()
+        .transport(transport)
+        .middleware(Delay(Duration::from_millis(50)))
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn merges_identical_concurrent_calls() {
+    let transport = Arc::new(MemoryTransport::new());
+    let client = client(transport.clone());
+
+    let (a, b) = futures::join!(
+        client.raw_get::<serde_json::Value>("runs", &[]),
+        client.raw_get::<serde_json::Value>("runs", &[]),
+    );
+
+    assert!(a.is_ok() && b.is_ok());
+    assert_eq!(transport.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn keeps_calls_of_different_priorities_apart() {
+    let transport = Arc::new(MemoryTransport::new());
+    let client = client(transport.clone());
+    let background = client.with_priority(Priority::Low);
+    let urgent = client.with_priority(Priority::High);
+
+    let (low, high) = futures::join!(
+        background.raw_get::<serde_json::Value>("runs", &[]),
+        urgent.raw_get::<serde_json::Value>("runs", &[]),
+    );
+
+    assert!(low.is_ok() && high.is_ok());
+
+    let priorities: Vec<Priority> = transport
+        .requests()
+        .iter()
+        .map(|request| request.priority)
+        .collect();
+    assert_eq!(priorities, [Priority::Low, Priority::High]);
+}
+
+#[tokio::test]
+async fn abandoned_calls_release_their_place_in_the_queue() {
+    let transport = Arc::new(MemoryTransport::new());
+
+    for path in ["runs", "games", "users"] {
+        transport.respond(
+            Method::GET,
+            &format!("{}/{}", BASE, path),
+            response(200, r#"{"data":[]}"#),
+        );
+    }
+
+    let client = SrcClient::builder()
+        .transport(transport.clone())
+        .rate_limit(RateLimit {
+            capacity: 1,
+            period: Duration::from_millis(200),
+            ..RateLimit::default()
+        })
+        .build()
+        .unwrap();
+
+    client
+        .raw_get::<serde_json::Value>("runs", &[])
+        .await
+        .unwrap();
+
+    // waits for the rate limiter until it is given up on
+    let abandoned = tokio::time::timeout(
+        Duration::from_millis(20),
+        client.raw_get::<serde_json::Value>("games", &[]),
+    )
+    .await;
+
+    assert!(abandoned.is_err());
+    assert!(client
+        .rate_limiter()
+        .queue_depths()
+        .iter()
+        .all(|&(_, depth)| depth == 0));
+
+    tokio::time::timeout(
+        Duration::from_secs(1),
+        client.raw_get::<serde_json::Value>("users", &[]),
+    )
+    .await
+    .expect("the abandoned call must not hold up the queue")
+    .unwrap();
+
+    assert_eq!(transport.requests().len(), 2);
+}
@@ -0,0 +1,82 @@
+#![allow(dead_code)]
+
+use futures::future::BoxFuture;
+use srcapi_plus::error::SrcError;
+use srcapi_plus::middleware::{Middleware, Next};
+use srcapi_plus::transport::{HttpRequest, HttpResponse, StatusCode};
+use std::path::PathBuf;
+use std::time::Duration;
+
+pub const BASE: &str = "https://www.speedrun.com/api/v1";
+
+pub fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+pub fn response(status: u16, body: &str) -> HttpResponse {
+    HttpResponse::new(StatusCode::from_u16(status).unwrap(), body.to_string())
+}
+
+/// Holds every request back for a while, so concurrent calls overlap.
+pub struct Delay(pub Duration);
+
+impl Middleware for Delay {
+    fn handle<'a>(
+        &'a self,
+        request: HttpRequest,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<HttpResponse, SrcError>> {
+        Box::pin(async move {
+            tokio::time::sleep(self.0).await;
+            next.run(request).await
+        })
+    }
+}
+
+pub fn user(id: &str) -> String {
+    format!(
+        r##"{{"id":"{id}","names":{{"international":"{id}","japanese":null}},"supporterAnimation":false,"pronouns":"","weblink":"https://www.speedrun.com/user/{id}","name-style":{{"style":"solid","color-from":{{"light":"#000","dark":"#fff"}},"color-to":{{"light":"#000","dark":"#fff"}}}},"role":"user","signup":"2020-01-01T00:00:00Z","location":{{"country":{{"code":"nl","names":{{"international":"Netherlands","japanese":null}}}},"region":null}},"twitch":null,"hitbox":null,"youtube":null,"twitter":null,"speedrunslive":null,"assets":{{"icon":{{"uri":null}},"supporterIcon":null,"image":{{"uri":null}}}},"links":[{{"rel":"self","uri":"{BASE}/users/{id}"}}]}}"##
+    )
+}
+
+/// A list page of users, linking to `next` if there is one.
+pub fn users_page(ids: &[&str], offset: u32, next: Option<&str>) -> String {
+    let data: Vec<String> = ids.iter().map(|id| user(id)).collect();
+    let links = match next {
+        Some(next) => format!(r#"[{{"rel":"next","uri":"{}"}}]"#, next),
+        None => "[]".to_string(),
+    };
+
+    format!(
+        r#"{{"data":[{}],"pagination":{{"offset":{},"max":{},"size":{},"links":{}}}}}"#,
+        data.join(","),
+        offset,
+        ids.len(),
+        ids.len(),
+        links
+    )
+}
+
+pub fn game(id: &str) -> String {
+    format!(
+        r#"{{"id":"{id}","names":{{"international":"Game \"{id}\"","japanese":"ゲーム","twitch":"Game {id}"}},"abbreviation":"{id}","weblink":"https://www.speedrun.com/{id}","released":2004,"release-date":"2004-01-01","ruleset":{{"show-milliseconds":false,"require-verification":true,"require-video":false,"run-times":["realtime","ingame"],"default-time":"realtime","emulators-allowed":false}},"romhack":false,"gametypes":[],"platforms":["p1","p2"],"regions":["r1"],"genres":[],"engines":[],"developers":["d1"],"publishers":["p1"],"moderators":{{"m1":"super-moderator","m2":"moderator"}},"created":"2015-01-01T00:00:00Z","assets":{{"logo":{{"uri":"https://www.speedrun.com/{id}/logo.png","width":64,"height":64}},"cover-tiny":null,"cover-small":null,"cover-medium":null,"cover-large":null,"icon":null,"trophy-1st":null,"trophy-2nd":null,"trophy-3rd":null,"trophy-4th":null,"foreground":null}},"links":[{{"rel":"self","uri":"{BASE}/games/{id}"}}]}}"#
+    )
+}
+
+/// A list page of `items`, which are JSON objects.
+pub fn page(items: &[String]) -> String {
+    format!(
+        r#"{{"data":[{}],"pagination":{{"offset":0,"max":{},"size":{},"links":[]}}}}"#,
+        items.join(","),
+        items.len(),
+        items.len()
+    )
+}
+
+pub fn run(id: &str) -> String {
+    format!(
+        r#"{{"id":"{id}","weblink":"https://www.speedrun.com/run/{id}","game":"g1","level":null,"category":"c1","videos":{{"links":[{{"uri":"https://youtu.be/x"}}]}},"comment":null,"status":{{"status":"verified","examiner":"u2","verify-date":"2020-01-02T00:00:00Z"}},"players":[{{"rel":"user","id":"u1","uri":"{BASE}/users/u1"}},{{"rel":"guest","name":"bob","uri":"{BASE}/guests/bob"}}],"date":"2020-01-01","submitted":"2020-01-01T12:00:00Z","times":{{"primary":"PT1M30S","primary_t":90,"realtime":"PT1M30S","realtime_t":90,"realtime_noloads":null,"realtime_noloads_t":0,"ingame":null,"ingame_t":0}},"system":{{"platform":"p1","emulated":false,"region":null}},"splits":null,"values":{{"v1":"x1"}},"links":[{{"rel":"self","uri":"{BASE}/runs/{id}"}}]}}"#
+    )
+}
@@ -0,0 +1,79 @@
+use srcapi_plus::cache::{CacheStore, CachedResponse, DiskCache};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("srcapi-plus-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+fn response(body: &str) -> CachedResponse {
+    CachedResponse {
+        status: 200,
+        body: body.to_string(),
+        fetched_at: SystemTime::now(),
+        etag: None,
+        last_modified: None,
+    }
+}
+
+#[test]
+fn round_trips_entries() {
+    let dir = temp_dir("round-trip");
+    let cache = DiskCache::open(&dir, 1 << 20).unwrap();
+
+    cache.insert("a", response("{}"));
+
+    assert_eq!(cache.get("a").unwrap().body, "{}");
+    assert!(cache.get("b").is_none());
+
+    cache.invalidate("a");
+    assert!(cache.get("a").is_none());
+
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn evicts_oldest_entries_over_max_size() {
+    let dir = temp_dir("evict");
+    let cache = DiskCache::open(&dir, 4096).unwrap();
+    let body = "x".repeat(500);
+
+    for i in 0..20 {
+        cache.insert(&format!("key-{}", i), response(&body));
+    }
+
+    assert!(cache.size() <= 4096);
+    assert!(cache.get("key-19").is_some());
+    assert!(cache.get("key-0").is_none());
+
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn sweeps_stale_temp_files() {
+    let dir = temp_dir("sweep");
+    fs::create_dir_all(&dir).unwrap();
+
+    let stale = dir.join("0000000000000000.1234.0.tmp");
+    let fresh = dir.join("0000000000000001.1234.1.tmp");
+    fs::write(&stale, "partial").unwrap();
+    fs::write(&fresh, "partial").unwrap();
+
+    let old = SystemTime::now() - Duration::from_secs(2 * 60 * 60);
+    fs::File::options()
+        .write(true)
+        .open(&stale)
+        .unwrap()
+        .set_modified(old)
+        .unwrap();
+
+    let _cache = DiskCache::open(&dir, 1 << 20).unwrap();
+
+    assert!(!stale.exists());
+    assert!(fresh.exists());
+
+    let _ = fs::remove_dir_all(dir);
+}
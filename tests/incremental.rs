@@ -0,0 +1,171 @@
+mod common;
+
+use common::{response, users_page, BASE};
+use futures::future::BoxFuture;
+use futures::{StreamExt, TryStreamExt};
+use srcapi_plus::cassette::Cassette;
+use srcapi_plus::client::SrcClient;
+use srcapi_plus::error::SrcError;
+use srcapi_plus::middleware::{Middleware, Next};
+use srcapi_plus::pagination::Paging;
+use srcapi_plus::query::UsersQuery;
+use srcapi_plus::retry::RetryPolicy;
+use srcapi_plus::transport::{HttpRequest, HttpResponse, MemoryTransport, Method};
+use srcapi_plus::users::User;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn incremental() -> Paging {
+    Paging {
+        incremental: true,
+        ..Paging::default()
+    }
+}
+
+fn by_name() -> UsersQuery {
+    UsersQuery {
+        name: Some("a".to_string()),
+        ..UsersQuery::default()
+    }
+}
+
+/// Answers every request itself, without overriding the streaming path.
+struct Canned;
+
+impl Middleware for Canned {
+    fn handle<'a>(
+        &'a self,
+        _request: HttpRequest,
+        _next: Next<'a>,
+    ) -> BoxFuture<'a, Result<HttpResponse, SrcError>> {
+        Box::pin(async { Ok(response(200, &users_page(&["canned"], 0, None))) })
+    }
+}
+
+#[tokio::test]
+async fn follows_next_links_while_decoding_incrementally() {
+    let transport = Arc::new(MemoryTransport::new());
+    let second = format!("{}/users?name=a&offset=2", BASE);
+
+    transport.respond(
+        Method::GET,
+        &format!("{}/users?name=a", BASE),
+        response(200, &users_page(&["a", "b"], 0, Some(&second))),
+    );
+    transport.respond(
+        Method::GET,
+        &second,
+        response(200, &users_page(&["c"], 2, None)),
+    );
+
+    let client = SrcClient::builder().transport(transport).build().unwrap();
+
+    let users: Vec<User> = client
+        .list_stream::<User>(by_name(), incremental())
+        .try_collect()
+        .await
+        .unwrap();
+
+    let ids: Vec<String> = users.iter().map(|user| user.id.to_string()).collect();
+    assert_eq!(ids, ["a", "b", "c"]);
+}
+
+#[tokio::test]
+async fn replays_pages_from_a_cassette() {
+    let transport = Arc::new(MemoryTransport::new());
+    let cassette = Cassette::replay(common::fixture("users_pages.json")).unwrap();
+
+    let client = SrcClient::builder()
+        .transport(transport.clone())
+        .cassette(cassette)
+        .build()
+        .unwrap();
+
+    let paging = Paging {
+        max: Some(2),
+        ..incremental()
+    };
+
+    let users: Vec<User> = client
+        .list_stream::<User>(by_name(), paging)
+        .try_collect()
+        .await
+        .unwrap();
+
+    let ids: Vec<String> = users.iter().map(|user| user.id.to_string()).collect();
+    assert_eq!(ids, ["a1", "a2", "a3"]);
+    assert!(transport.requests().is_empty());
+}
+
+#[tokio::test]
+async fn retries_server_errors() {
+    let transport = Arc::new(MemoryTransport::new());
+    let url = format!("{}/users?name=a", BASE);
+
+    transport.respond(
+        Method::GET,
+        &url,
+        response(503, r#"{"status":503,"message":"busy","links":[]}"#),
+    );
+    transport.respond(
+        Method::GET,
+        &url,
+        response(200, &users_page(&["a"], 0, None)),
+    );
+
+    let client = SrcClient::builder()
+        .transport(transport.clone())
+        .retry_policy(RetryPolicy::default().base_delay(Duration::from_millis(1)))
+        .build()
+        .unwrap();
+
+    let users: Vec<User> = client
+        .list_stream::<User>(by_name(), incremental())
+        .try_collect()
+        .await
+        .unwrap();
+
+    assert_eq!(users.len(), 1);
+    assert_eq!(transport.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn goes_through_the_middleware_set_on_the_builder() {
+    let transport = Arc::new(MemoryTransport::new());
+
+    let client = SrcClient::builder()
+        .transport(transport.clone())
+        .middleware(Canned)
+        .build()
+        .unwrap();
+
+    let users: Vec<User> = client
+        .list_stream::<User>(by_name(), incremental())
+        .try_collect()
+        .await
+        .unwrap();
+
+    let ids: Vec<String> = users.iter().map(|user| user.id.to_string()).collect();
+    assert_eq!(ids, ["canned"]);
+    assert!(transport.requests().is_empty());
+}
+
+#[tokio::test]
+async fn fails_with_the_api_error() {
+    let transport = Arc::new(MemoryTransport::new());
+
+    transport.respond(
+        Method::GET,
+        &format!("{}/users?name=a", BASE),
+        response(404, r#"{"status":404,"message":"not found","links":[]}"#),
+    );
+
+    let client = SrcClient::builder().transport(transport).build().unwrap();
+
+    let mut users = Box::pin(client.list_stream::<User>(by_name(), incremental()));
+
+    assert!(matches!(
+        users.next().await,
+        Some(Err(SrcError::Api { status: 404, .. }))
+    ));
+}
@@ -0,0 +1,125 @@
+#![cfg(feature = "metrics")]
+
+mod common;
+
+use common::{response, Delay};
+use futures::future::join_all;
+use srcapi_plus::cache::CacheConfig;
+use srcapi_plus::client::SrcClient;
+use srcapi_plus::metrics::Metrics;
+use srcapi_plus::response::Response;
+use srcapi_plus::runs::Run;
+use srcapi_plus::transport::{MemoryTransport, Method};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[tokio::test]
+async fn merged_calls_count_as_one_request() {
+    let transport = Arc::new(MemoryTransport::new());
+    transport.respond(
+        Method::GET,
+        "https://www.speedrun.com/api/v1/runs",
+        response(200, r#"{"data":[]}"#),
+    );
+
+    let metrics = Metrics::new();
+    let client = SrcClient::builder()
+        .transport(transport.clone())
+        .middleware(Delay(Duration::from_millis(50)))
+        .metrics(metrics.clone())
+        .build()
+        .unwrap();
+
+    let calls = (0..5).map(|_| client.raw_get::<serde_json::Value>("runs", &[]));
+    let results: Vec<Result<Response<_>, _>> = join_all(calls).await;
+
+    assert!(results.iter().all(Result::is_ok));
+    assert_eq!(transport.requests().len(), 1);
+    assert!(metrics
+        .render()
+        .contains(r#"srcapi_requests_total{endpoint="raw",status="200"} 1"#));
+}
+
+#[tokio::test]
+async fn single_gets_are_labelled_apart_from_lists() {
+    let transport = Arc::new(MemoryTransport::new());
+    transport.respond(
+        Method::GET,
+        "https://www.speedrun.com/api/v1/runs/r1",
+        response(404, r#"{"status":404,"message":"not found","links":[]}"#),
+    );
+    transport.respond(
+        Method::GET,
+        "https://www.speedrun.com/api/v1/runs",
+        response(200, r#"{"data":[]}"#),
+    );
+
+    let metrics = Metrics::new();
+    let client = SrcClient::builder()
+        .transport(transport)
+        .metrics(metrics.clone())
+        .build()
+        .unwrap();
+
+    assert!(client.get::<Run>("r1").await.is_err());
+    assert!(client.list::<Run>(()).await.unwrap().is_empty());
+
+    let rendered = metrics.render();
+    assert!(rendered.contains(r#"srcapi_requests_total{endpoint="runs/{id}",status="404"} 1"#));
+    assert!(rendered.contains(r#"srcapi_requests_total{endpoint="runs",status="200"} 1"#));
+}
+
+#[tokio::test]
+async fn cache_hits_are_not_counted_as_requests() {
+    let transport = Arc::new(MemoryTransport::new());
+    transport.respond(
+        Method::GET,
+        "https://www.speedrun.com/api/v1/runs",
+        response(200, r#"{"data":[]}"#),
+    );
+
+    let metrics = Metrics::new();
+    let client = SrcClient::builder()
+        .transport(transport)
+        .cache(CacheConfig::new(Duration::from_secs(60)))
+        .metrics(metrics.clone())
+        .build()
+        .unwrap();
+
+    for _ in 0..3 {
+        client.list::<Run>(()).await.unwrap();
+    }
+
+    let rendered = metrics.render();
+    assert!(rendered.contains(r#"srcapi_requests_total{endpoint="runs",status="200"} 1"#));
+    assert!(rendered.contains(r#"srcapi_cache_lookups_total{endpoint="runs",result="hit"} 2"#));
+    assert!(rendered.contains(r#"srcapi_cache_lookups_total{endpoint="runs",result="miss"} 1"#));
+}
+
+#[tokio::test]
+async fn stale_responses_served_offline_count_as_hits() {
+    let transport = Arc::new(MemoryTransport::new());
+    transport.respond(
+        Method::GET,
+        "https://www.speedrun.com/api/v1/runs",
+        response(200, r#"{"data":[]}"#),
+    );
+
+    let metrics = Metrics::new();
+    let client = SrcClient::builder()
+        .transport(transport)
+        .cache(CacheConfig::new(Duration::ZERO))
+        .metrics(metrics.clone())
+        .build()
+        .unwrap();
+
+    client.list::<Run>(()).await.unwrap();
+    client.with_offline(true).list::<Run>(()).await.unwrap();
+
+    let stats = client.cache().unwrap().stats();
+    assert_eq!((stats.hits, stats.misses), (1, 1));
+
+    let rendered = metrics.render();
+    assert!(rendered.contains(r#"srcapi_cache_lookups_total{endpoint="runs",result="hit"} 1"#));
+    assert!(rendered.contains(r#"srcapi_cache_lookups_total{endpoint="runs",result="miss"} 1"#));
+}
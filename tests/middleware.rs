@@ -0,0 +1,162 @@
+mod common;
+
+use common::{response, user, BASE};
+use futures::future::BoxFuture;
+use srcapi_plus::cache::CacheConfig;
+use srcapi_plus::client::SrcClient;
+use srcapi_plus::error::SrcError;
+use srcapi_plus::middleware::{Middleware, Next};
+use srcapi_plus::response::Freshness;
+use srcapi_plus::transport::header::{HeaderName, HeaderValue};
+use srcapi_plus::transport::{HttpRequest, HttpResponse, MemoryTransport, Method};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn data(item: &str) -> String {
+    format!(r#"{{"data":{}}}"#, item)
+}
+
+fn transport() -> Arc<MemoryTransport> {
+    let transport = Arc::new(MemoryTransport::new());
+
+    transport.respond(
+        Method::GET,
+        &format!("{}/users/a", BASE),
+        response(200, &data(&user("a"))),
+    );
+
+    transport
+}
+
+/// Answers every request itself.
+struct Canned;
+
+impl Middleware for Canned {
+    fn handle<'a>(
+        &'a self,
+        _request: HttpRequest,
+        _next: Next<'a>,
+    ) -> BoxFuture<'a, Result<HttpResponse, SrcError>> {
+        Box::pin(async { Ok(response(200, &data(&user("canned")))) })
+    }
+}
+
+/// Renames user `a` to `b` in every response.
+struct Rename;
+
+impl Middleware for Rename {
+    fn handle<'a>(
+        &'a self,
+        request: HttpRequest,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<HttpResponse, SrcError>> {
+        Box::pin(async move {
+            let mut response = next.run(request).await?;
+
+            let body =
+                String::from_utf8_lossy(&response.body).replace(r#""id":"a""#, r#""id":"b""#);
+            response.body = body.into();
+
+            Ok(response)
+        })
+    }
+}
+
+/// Tags every request with a header.
+struct Tag;
+
+impl Middleware for Tag {
+    fn handle<'a>(
+        &'a self,
+        mut request: HttpRequest,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<HttpResponse, SrcError>> {
+        request.headers.insert(
+            HeaderName::from_static("x-tag"),
+            HeaderValue::from_static("dashboard"),
+        );
+
+        next.run(request)
+    }
+}
+
+/// Counts the requests passing through.
+#[derive(Clone, Default)]
+struct Count(Arc<AtomicUsize>);
+
+impl Middleware for Count {
+    fn handle<'a>(
+        &'a self,
+        request: HttpRequest,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<HttpResponse, SrcError>> {
+        self.0.fetch_add(1, Ordering::Relaxed);
+
+        next.run(request)
+    }
+}
+
+#[tokio::test]
+async fn can_answer_without_calling_the_rest_of_the_chain() {
+    let transport = transport();
+
+    let client = SrcClient::builder()
+        .transport(transport.clone())
+        .middleware(Canned)
+        .build()
+        .unwrap();
+
+    let user = client.get_user("a".to_string()).await.unwrap();
+
+    assert_eq!(user.id.to_string(), "canned");
+    assert!(transport.requests().is_empty());
+}
+
+#[tokio::test]
+async fn can_change_the_response() {
+    let client = SrcClient::builder()
+        .transport(transport())
+        .middleware(Rename)
+        .build()
+        .unwrap();
+
+    let user = client.get_user("a".to_string()).await.unwrap();
+
+    assert_eq!(user.id.to_string(), "b");
+}
+
+#[tokio::test]
+async fn can_change_the_request() {
+    let transport = transport();
+
+    let client = SrcClient::builder()
+        .transport(transport.clone())
+        .middleware(Tag)
+        .build()
+        .unwrap();
+
+    client.get_user("a".to_string()).await.unwrap();
+
+    assert_eq!(transport.requests()[0].headers["x-tag"], "dashboard");
+}
+
+#[tokio::test]
+async fn wraps_the_cache() {
+    let transport = transport();
+    let count = Count::default();
+
+    let client = SrcClient::builder()
+        .transport(transport.clone())
+        .middleware(count.clone())
+        .cache(CacheConfig::new(Duration::from_secs(60)))
+        .build()
+        .unwrap();
+
+    client.get_user("a".to_string()).await.unwrap();
+    let cached = client.get_user("a".to_string()).await.unwrap();
+
+    assert_eq!(cached.freshness, Freshness::Cached);
+    assert_eq!(count.0.load(Ordering::Relaxed), 2);
+    assert_eq!(transport.requests().len(), 1);
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use common::{response, user, BASE};
+use srcapi_plus::cache::CacheConfig;
+use srcapi_plus::client::SrcClient;
+use srcapi_plus::error::SrcError;
+use srcapi_plus::response::Freshness;
+use srcapi_plus::transport::{MemoryTransport, Method};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn client(transport: Arc<MemoryTransport>) -> SrcClient {
+    SrcClient::builder()
+        .transport(transport)
+        .cache(CacheConfig::new(Duration::ZERO))
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn fails_without_a_cached_response() {
+    let transport = Arc::new(MemoryTransport::new());
+    let client = client(transport.clone()).with_offline(true);
+
+    let err = client.get_user("a".to_string()).await.unwrap_err();
+
+    match err {
+        SrcError::Offline { url } => assert_eq!(url, format!("{}/users/a", BASE)),
+        other => panic!("expected an offline error, got {:?}", other),
+    }
+    assert!(transport.requests().is_empty());
+}
+
+#[tokio::test]
+async fn serves_stale_responses_without_asking_the_api() {
+    let transport = Arc::new(MemoryTransport::new());
+    let client = client(transport.clone());
+
+    transport.respond(
+        Method::GET,
+        &format!("{}/users/a", BASE),
+        response(200, &format!(r#"{{"data":{}}}"#, user("a"))),
+    );
+
+    client.get_user("a".to_string()).await.unwrap();
+
+    let offline = client.with_offline(true);
+    let user = offline.get_user("a".to_string()).await.unwrap();
+
+    assert_eq!(user.freshness, Freshness::Cached);
+    assert_eq!(user.id.to_string(), "a");
+    assert_eq!(transport.requests().len(), 1);
+
+    // served as is, so it counts as a hit however old it is
+    let stats = offline.cache().unwrap().stats();
+    assert_eq!((stats.hits, stats.misses), (1, 1));
+}
@@ -0,0 +1,87 @@
+mod common;
+
+use common::{response, users_page, BASE};
+use futures::{StreamExt, TryStreamExt};
+use srcapi_plus::client::SrcClient;
+use srcapi_plus::error::{QueryError, SrcError};
+use srcapi_plus::pagination::Paging;
+use srcapi_plus::query::UsersQuery;
+use srcapi_plus::transport::{MemoryTransport, Method};
+use srcapi_plus::users::User;
+use std::sync::Arc;
+
+const MIRROR: &str = "http://localhost:8080/api/v1";
+
+fn by_name() -> UsersQuery {
+    UsersQuery {
+        name: Some("a".to_string()),
+        ..UsersQuery::default()
+    }
+}
+
+fn mirrored(transport: Arc<MemoryTransport>) -> SrcClient {
+    SrcClient::builder()
+        .base_url(MIRROR)
+        .api_key("secret")
+        .transport(transport)
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn moves_next_links_onto_the_base_url() {
+    let transport = Arc::new(MemoryTransport::new());
+
+    // the mirror still names speedrun.com in its links
+    transport.respond(
+        Method::GET,
+        &format!("{}/users?name=a", MIRROR),
+        response(
+            200,
+            &users_page(&["a"], 0, Some(&format!("{}/users?name=a&offset=1", BASE))),
+        ),
+    );
+    transport.respond(
+        Method::GET,
+        &format!("{}/users?name=a&offset=1", MIRROR),
+        response(200, &users_page(&["b"], 1, None)),
+    );
+
+    let users: Vec<User> = mirrored(transport.clone())
+        .list_stream::<User>(by_name(), Paging::default())
+        .try_collect()
+        .await
+        .unwrap();
+
+    assert_eq!(users.len(), 2);
+    assert!(transport
+        .requests()
+        .iter()
+        .all(|request| request.url.as_str().starts_with(MIRROR)));
+}
+
+#[tokio::test]
+async fn refuses_next_links_to_other_hosts() {
+    let transport = Arc::new(MemoryTransport::new());
+    let elsewhere = "https://example.com/api/v1/users?name=a&offset=1";
+
+    transport.respond(
+        Method::GET,
+        &format!("{}/users?name=a", MIRROR),
+        response(200, &users_page(&["a"], 0, Some(elsewhere))),
+    );
+
+    let client = mirrored(transport.clone());
+    let results: Vec<_> = client
+        .list_stream::<User>(by_name(), Paging::default())
+        .collect()
+        .await;
+
+    assert_eq!(results.len(), 2);
+    assert!(results[0].is_ok());
+    assert!(matches!(
+        &results[1],
+        Err(SrcError::Query(QueryError::InvalidPath { path })) if path == elsewhere
+    ));
+    assert_eq!(transport.requests().len(), 1);
+}
@@ -0,0 +1,46 @@
+use srcapi_plus::client::SrcClient;
+use srcapi_plus::transport::MemoryTransport;
+
+fn client() -> SrcClient {
+    SrcClient::builder()
+        .transport(MemoryTransport::new())
+        .profile("bot", "bot-key")
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn ad_hoc_keys_get_a_stable_identity() {
+    // 64-bit FNV-1a of "abc"; must not change between builds, as it is part
+    // of persistent cache keys
+    assert_eq!(
+        client().with_api_key("abc").identity(),
+        Some("key-e71fa2190541574b")
+    );
+}
+
+#[test]
+fn profiles_are_identified_by_name_and_key() {
+    let client = client();
+
+    assert_eq!(client.identity(), None);
+    assert_eq!(
+        client.with_profile("bot").unwrap().identity(),
+        Some("bot-f50a96669d8fba00")
+    );
+    assert!(client.with_profile("missing").is_err());
+}
+
+#[test]
+fn rotating_a_profile_key_changes_its_identity() {
+    let rotated = SrcClient::builder()
+        .transport(MemoryTransport::new())
+        .profile("bot", "new-bot-key")
+        .build()
+        .unwrap();
+
+    assert_ne!(
+        client().with_profile("bot").unwrap().identity(),
+        rotated.with_profile("bot").unwrap().identity()
+    );
+}
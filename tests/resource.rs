@@ -0,0 +1,56 @@
+mod common;
+
+use common::{response, run, BASE};
+use srcapi_plus::client::SrcClient;
+use srcapi_plus::runs::{Player, Run, Status};
+use srcapi_plus::transport::{MemoryTransport, Method};
+use std::sync::Arc;
+
+fn client(transport: &Arc<MemoryTransport>) -> SrcClient {
+    SrcClient::builder()
+        .transport(transport.clone())
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn user_pbs_are_runs_with_places() {
+    let transport = Arc::new(MemoryTransport::new());
+    transport.respond(
+        Method::GET,
+        &format!("{}/users/u1/personal-bests?top=1", BASE),
+        response(
+            200,
+            &format!(r#"{{"data":[{{"place":1,"run":{}}}]}}"#, run("r1")),
+        ),
+    );
+
+    let pbs = client(&transport)
+        .get_user_pbs("u1".to_string(), Some(1), None, None)
+        .await
+        .unwrap()
+        .into_inner();
+
+    assert_eq!(pbs.len(), 1);
+    assert_eq!(pbs[0].place, 1);
+
+    let run = &pbs[0].run;
+    assert_eq!(run.id.to_string(), "r1");
+    assert_eq!(run.times.primary_t, 90.0);
+    assert!(matches!(run.status, Status::Verified { .. }));
+    assert!(matches!(&run.players[1], Player::Guest { name, .. } if name == "bob"));
+}
+
+#[tokio::test]
+async fn resources_use_their_default_paths() {
+    let transport = Arc::new(MemoryTransport::new());
+    transport.respond(
+        Method::GET,
+        &format!("{}/runs/r1", BASE),
+        response(200, &format!(r#"{{"data":{}}}"#, run("r1"))),
+    );
+
+    let run = client(&transport).get::<Run>("r1").await.unwrap();
+
+    assert_eq!(run.id.to_string(), "r1");
+}
@@ -0,0 +1,127 @@
+mod common;
+
+use common::{response, user, BASE};
+use srcapi_plus::client::SrcClient;
+use srcapi_plus::error::SrcError;
+use srcapi_plus::retry::RetryPolicy;
+use srcapi_plus::transport::header::{HeaderValue, RETRY_AFTER};
+use srcapi_plus::transport::{MemoryTransport, Method, StatusCode};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// What the `on_attempt` callback saw of one attempt.
+#[derive(Debug, PartialEq)]
+struct Seen {
+    attempt: u32,
+    status: Option<StatusCode>,
+    failed: bool,
+    retry_in: Option<Duration>,
+}
+
+fn client(
+    transport: Arc<MemoryTransport>,
+    policy: RetryPolicy,
+) -> (SrcClient, Arc<Mutex<Vec<Seen>>>) {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let log = seen.clone();
+
+    let policy = policy.jitter(0.0).on_attempt(move |attempt| {
+        log.lock().unwrap().push(Seen {
+            attempt: attempt.attempt,
+            status: attempt.status,
+            failed: attempt.error.is_some(),
+            retry_in: attempt.retry_in,
+        });
+    });
+
+    let client = SrcClient::builder()
+        .transport(transport)
+        .retry_policy(policy)
+        .build()
+        .unwrap();
+
+    (client, seen)
+}
+
+#[tokio::test]
+async fn reports_every_attempt() {
+    let transport = Arc::new(MemoryTransport::new());
+    let url = format!("{}/users/a", BASE);
+
+    transport.respond(Method::GET, &url, response(503, ""));
+    transport.respond(
+        Method::GET,
+        &url,
+        response(200, &format!(r#"{{"data":{}}}"#, user("a"))),
+    );
+
+    let policy = RetryPolicy::default().base_delay(Duration::from_millis(1));
+    let (client, seen) = client(transport.clone(), policy);
+
+    client.get_user("a".to_string()).await.unwrap();
+
+    assert_eq!(
+        *seen.lock().unwrap(),
+        [
+            Seen {
+                attempt: 1,
+                status: Some(StatusCode::SERVICE_UNAVAILABLE),
+                failed: true,
+                retry_in: Some(Duration::from_millis(1)),
+            },
+            Seen {
+                attempt: 2,
+                status: Some(StatusCode::OK),
+                failed: false,
+                retry_in: None,
+            },
+        ]
+    );
+    assert_eq!(transport.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn waits_as_long_as_retry_after_says() {
+    let transport = Arc::new(MemoryTransport::new());
+    let url = format!("{}/users/a", BASE);
+
+    transport.respond(
+        Method::GET,
+        &url,
+        response(420, "").with_header(RETRY_AFTER, HeaderValue::from_static("0")),
+    );
+    transport.respond(
+        Method::GET,
+        &url,
+        response(200, &format!(r#"{{"data":{}}}"#, user("a"))),
+    );
+
+    let policy = RetryPolicy::default().base_delay(Duration::from_secs(60));
+    let (client, seen) = client(transport, policy);
+
+    client.get_user("a".to_string()).await.unwrap();
+
+    assert_eq!(seen.lock().unwrap()[0].retry_in, Some(Duration::ZERO));
+}
+
+#[tokio::test]
+async fn gives_up_after_max_attempts() {
+    let transport = Arc::new(MemoryTransport::new());
+
+    transport.respond(Method::GET, &format!("{}/users/a", BASE), response(503, ""));
+
+    let policy = RetryPolicy::default()
+        .max_attempts(2)
+        .base_delay(Duration::from_millis(1));
+    let (client, seen) = client(transport.clone(), policy);
+
+    let err = client.get_user("a".to_string()).await.unwrap_err();
+
+    assert!(matches!(err, SrcError::Api { status: 503, .. }));
+    assert_eq!(transport.requests().len(), 2);
+
+    let seen = seen.lock().unwrap();
+    assert_eq!(seen.len(), 2);
+    assert!(seen[1].failed);
+    assert_eq!(seen[1].retry_in, None);
+}